@@ -0,0 +1,275 @@
+// Capture filtering and redaction.
+//
+// For a keystroke/accessibility capture tool, forwarding every extracted
+// string straight to storage means passwords, card numbers and private-app
+// text get persisted. This stage sits between `extract_text_from_event` and
+// the capture channel:
+//
+//   1. An application allow/deny list keyed on application name / window class
+//      drops captures from sensitive apps (password managers, banking apps)
+//      entirely.
+//   2. Secure fields (a password element type/role) are dropped.
+//   3. A redaction pass masks regex-matched secrets — Luhn-valid card numbers,
+//      emails and API-key-looking tokens — in everything that survives.
+//
+// The rule set is data-driven so users can tune it from config without
+// recompiling, and dropped/redacted captures are counted.
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::debug;
+
+/// User-tunable filtering rules, loadable from config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterConfig {
+    /// If non-empty, only applications in this list are captured at all.
+    #[serde(default)]
+    pub app_allow_list: Vec<String>,
+    /// Applications (by name or window class) whose captures are dropped.
+    #[serde(default)]
+    pub app_deny_list: Vec<String>,
+    /// Accessibility element types/roles that denote a secure field.
+    #[serde(default = "default_secure_roles")]
+    pub secure_roles: Vec<String>,
+    /// Whether to mask secrets in text that passes the app filter.
+    #[serde(default = "default_true")]
+    pub redact_secrets: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_secure_roles() -> Vec<String> {
+    vec![
+        "password".to_string(),
+        "secure".to_string(),
+        "AXSecureTextField".to_string(),
+    ]
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            app_allow_list: Vec::new(),
+            app_deny_list: Vec::new(),
+            secure_roles: default_secure_roles(),
+            redact_secrets: true,
+        }
+    }
+}
+
+/// The outcome of running a capture through the filter.
+pub enum FilterOutcome {
+    /// The capture should be dropped entirely.
+    Drop,
+    /// The capture is kept, with `text` possibly redacted.
+    Keep { text: String, redacted: bool },
+}
+
+/// Compiled filter applying the configured rules and counting its actions.
+pub struct CaptureFilter {
+    config: FilterConfig,
+    secret_patterns: Vec<Regex>,
+    card_pattern: Regex,
+    dropped: AtomicU64,
+    redacted: AtomicU64,
+}
+
+impl CaptureFilter {
+    pub fn new(config: FilterConfig) -> Result<Self> {
+        // Email and API-key-looking tokens are masked outright; card numbers
+        // go through an extra Luhn check before masking.
+        let secret_patterns = vec![
+            Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}")
+                .context("invalid email pattern")?,
+            Regex::new(r"\b(?:sk|pk|api|key|token)[_-][A-Za-z0-9]{16,}\b")
+                .context("invalid api-key pattern")?,
+        ];
+        let card_pattern =
+            Regex::new(r"\b(?:\d[ -]?){13,19}\b").context("invalid card pattern")?;
+
+        Ok(Self {
+            config,
+            secret_patterns,
+            card_pattern,
+            dropped: AtomicU64::new(0),
+            redacted: AtomicU64::new(0),
+        })
+    }
+
+    /// Decide whether to keep a capture and, if so, redact its text.
+    pub fn apply(
+        &self,
+        application_name: &str,
+        window_class: Option<&str>,
+        element_type: &str,
+        element_role: &str,
+        text: &str,
+    ) -> FilterOutcome {
+        if self.should_drop(application_name, window_class, element_type, element_role) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            debug!("Dropped capture from {}", application_name);
+            return FilterOutcome::Drop;
+        }
+
+        if !self.config.redact_secrets {
+            return FilterOutcome::Keep {
+                text: text.to_string(),
+                redacted: false,
+            };
+        }
+
+        let (text, redacted) = self.redact(text);
+        if redacted {
+            self.redacted.fetch_add(1, Ordering::Relaxed);
+        }
+        FilterOutcome::Keep { text, redacted }
+    }
+
+    // App deny/allow list and secure-field check.
+    fn should_drop(
+        &self,
+        application_name: &str,
+        window_class: Option<&str>,
+        element_type: &str,
+        element_role: &str,
+    ) -> bool {
+        let matches_app = |rule: &String| {
+            application_name.eq_ignore_ascii_case(rule)
+                || window_class.is_some_and(|c| c.eq_ignore_ascii_case(rule))
+        };
+
+        if self.config.app_deny_list.iter().any(matches_app) {
+            return true;
+        }
+        if !self.config.app_allow_list.is_empty()
+            && !self.config.app_allow_list.iter().any(matches_app)
+        {
+            return true;
+        }
+        // Secure fields (password boxes) never get captured.
+        self.config
+            .secure_roles
+            .iter()
+            .any(|role| element_type.eq_ignore_ascii_case(role) || element_role.eq_ignore_ascii_case(role))
+    }
+
+    // Mask matched secrets, returning the text and whether anything changed.
+    fn redact(&self, text: &str) -> (String, bool) {
+        let mut out = text.to_string();
+        let mut redacted = false;
+
+        // Card numbers: only mask candidates that pass a Luhn check.
+        for m in self.card_pattern.find_iter(text) {
+            let digits: String = m.as_str().chars().filter(|c| c.is_ascii_digit()).collect();
+            if (13..=19).contains(&digits.len()) && luhn_valid(&digits) {
+                out = out.replace(m.as_str(), "[REDACTED_CARD]");
+                redacted = true;
+            }
+        }
+
+        for pattern in &self.secret_patterns {
+            if pattern.is_match(&out) {
+                out = pattern.replace_all(&out, "[REDACTED]").into_owned();
+                redacted = true;
+            }
+        }
+
+        (out, redacted)
+    }
+
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn redacted_count(&self) -> u64 {
+        self.redacted.load(Ordering::Relaxed)
+    }
+}
+
+// Standard Luhn checksum over a string of digits.
+fn luhn_valid(digits: &str) -> bool {
+    let mut sum = 0u32;
+    let mut double = false;
+    for c in digits.chars().rev() {
+        let mut d = match c.to_digit(10) {
+            Some(d) => d,
+            None => return false,
+        };
+        if double {
+            d *= 2;
+            if d > 9 {
+                d -= 9;
+            }
+        }
+        sum += d;
+        double = !double;
+    }
+    sum % 10 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deny_list_drops_capture() -> Result<()> {
+        let config = FilterConfig {
+            app_deny_list: vec!["1Password".to_string()],
+            ..Default::default()
+        };
+        let filter = CaptureFilter::new(config)?;
+        assert!(matches!(
+            filter.apply("1Password", None, "textbox", "text", "secret"),
+            FilterOutcome::Drop
+        ));
+        assert_eq!(filter.dropped_count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_secure_field_dropped() -> Result<()> {
+        let filter = CaptureFilter::new(FilterConfig::default())?;
+        assert!(matches!(
+            filter.apply("Safari", None, "password", "AXSecureTextField", "hunter2"),
+            FilterOutcome::Drop
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_redacts_card_and_email() -> Result<()> {
+        let filter = CaptureFilter::new(FilterConfig::default())?;
+        // 4111 1111 1111 1111 is a Luhn-valid test card.
+        let outcome = filter.apply(
+            "Notes",
+            None,
+            "textbox",
+            "text",
+            "pay 4111 1111 1111 1111 to a@b.com",
+        );
+        match outcome {
+            FilterOutcome::Keep { text, redacted } => {
+                assert!(redacted);
+                assert!(text.contains("[REDACTED_CARD]"));
+                assert!(text.contains("[REDACTED]"));
+                assert!(!text.contains("4111"));
+            }
+            FilterOutcome::Drop => panic!("should have kept redacted text"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_luhn_number_not_redacted() -> Result<()> {
+        let filter = CaptureFilter::new(FilterConfig::default())?;
+        let outcome = filter.apply("Notes", None, "textbox", "text", "order 1234567812345670000");
+        if let FilterOutcome::Keep { redacted, .. } = outcome {
+            // A random long number that fails Luhn must be left alone.
+            assert!(!redacted);
+        }
+        Ok(())
+    }
+}
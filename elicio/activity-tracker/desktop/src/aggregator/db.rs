@@ -1,16 +1,92 @@
-use anyhow::{Context, Result};
-use rusqlite::Connection;
+use anyhow::{bail, Context, Result};
+use include_dir::{include_dir, Dir};
+use r2d2::{Pool, PooledConnection};
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, Connection};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::path::Path;
-use tracing::info;
+use tracing::{info, warn};
 
-pub(crate) fn init_database(db_path: &str, encryption_key: &str) -> Result<Connection> {
-    let is_new_db = !Path::new(db_path).exists();
-    
-    // Open or create encrypted database
-    let conn = Connection::open(db_path)
-        .context("Failed to open database")?;
+/// Connection-pool sizing. Defaults mirror the relay configs we model on:
+/// a couple of warm connections with headroom for the capture writer, the
+/// retention sweeper, and ad-hoc readers.
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub min_conn: u32,
+    pub max_conn: u32,
+}
 
-    // Configure encryption and performance settings
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            min_conn: 1,
+            max_conn: 8,
+        }
+    }
+}
+
+/// A pooled handle to the encrypted SQLite database.
+///
+/// Every connection handed out by the pool has already run the SQLCipher
+/// `PRAGMA key`/cipher setup and the WAL performance pragmas in its
+/// customizer, so callers can `get()` a connection and use it immediately.
+/// WAL mode lets readers proceed while the capture writer commits, which the
+/// old single-`Connection` handle could not do.
+#[derive(Clone)]
+pub(crate) struct Database {
+    pool: Pool<SqliteConnectionManager>,
+}
+
+/// A connection checked out of the pool. Returned to the pool on drop.
+pub(crate) type PooledConn = PooledConnection<SqliteConnectionManager>;
+
+impl Database {
+    /// Open (or create) the encrypted database and build the pool, running
+    /// migrations once on a freshly checked-out connection.
+    pub(crate) fn new(
+        db_path: &str,
+        encryption_key: &str,
+        pool_config: &PoolConfig,
+    ) -> Result<Self> {
+        let is_new_db = !Path::new(db_path).exists();
+
+        let key = encryption_key.to_string();
+        let manager = SqliteConnectionManager::file(db_path)
+            .with_init(move |conn| configure_connection(conn, &key));
+
+        let pool = Pool::builder()
+            .min_idle(Some(pool_config.min_conn))
+            .max_size(pool_config.max_conn)
+            .build(manager)
+            .context("Failed to build connection pool")?;
+
+        if is_new_db {
+            info!("Creating new database at {}", db_path);
+        } else {
+            info!("Using existing database at {}", db_path);
+        }
+
+        // Migrations run once, on a single connection, before the pool is
+        // handed to the rest of the system.
+        let conn = pool.get().context("Failed to check out connection")?;
+        run_migrations(&conn)?;
+
+        Ok(Self { pool })
+    }
+
+    /// Check a connection out of the pool.
+    pub(crate) fn get(&self) -> Result<PooledConn> {
+        self.pool
+            .get()
+            .context("Failed to check out database connection")
+    }
+}
+
+/// Apply the SQLCipher key and the encryption/performance pragmas to a
+/// connection. Shared by the pool customizer so every pooled connection is
+/// configured identically.
+fn configure_connection(conn: &mut Connection, encryption_key: &str) -> Result<(), rusqlite::Error> {
     conn.execute_batch(&format!(
         "PRAGMA key = '{}';
          PRAGMA cipher_page_size = 4096;
@@ -20,24 +96,109 @@ pub(crate) fn init_database(db_path: &str, encryption_key: &str) -> Result<Conne
          PRAGMA cache_size = -2000;  -- Reserve 2MB for cache
          PRAGMA temp_store = MEMORY; -- Use memory for temp storage",
         encryption_key
-    )).context("Failed to configure encryption")?;
+    ))
+}
+
+// Migration files are embedded at compile time so a release binary carries its
+// own schema. Adding migration 003+ is a matter of dropping a `NNN_*.sql` file
+// in this directory — the runner discovers and orders it automatically.
+static MIGRATIONS: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/../db/migrations");
+
+// A single discovered migration, ordered by its numeric `NNN_` prefix.
+struct Migration {
+    version: i64,
+    description: String,
+    sql: String,
+    checksum: String,
+}
+
+/// Open a standalone encrypted connection, running migrations on it. Retained
+/// for callers (and tests) that want a single connection rather than the pool.
+pub(crate) fn init_database(db_path: &str, encryption_key: &str) -> Result<Connection> {
+    let is_new_db = !Path::new(db_path).exists();
+
+    // Open or create encrypted database
+    let mut conn = Connection::open(db_path)
+        .context("Failed to open database")?;
+
+    // Configure encryption and performance settings
+    configure_connection(&mut conn, encryption_key)
+        .context("Failed to configure encryption")?;
 
     if is_new_db {
         info!("Creating new database at {}", db_path);
-        run_migrations(&conn)?;
     } else {
         info!("Using existing database at {}", db_path);
-        verify_schema(&conn)?;
     }
 
+    // The versioned runner is idempotent: it applies only the migrations not
+    // yet recorded in schema_migrations, so it handles both a fresh DB and an
+    // existing one left part-way through an upgrade.
+    run_migrations(&conn)?;
+
     Ok(conn)
 }
 
-fn run_migrations(conn: &Connection) -> Result<()> {
-    let tx = conn.transaction()?;
+// Discover every `NNN_*.sql` migration, ordered by numeric version.
+fn discover_migrations() -> Result<Vec<Migration>> {
+    let mut migrations = Vec::new();
+
+    for file in MIGRATIONS.files() {
+        let name = file
+            .path()
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+
+        // Only `NNN_description.sql` files participate.
+        if !name.ends_with(".sql") {
+            continue;
+        }
+        let (prefix, rest) = match name.split_once('_') {
+            Some(parts) => parts,
+            None => {
+                warn!("Skipping migration file with no version prefix: {}", name);
+                continue;
+            }
+        };
+        let version: i64 = prefix
+            .parse()
+            .with_context(|| format!("Migration {} has a non-numeric version prefix", name))?;
+
+        let sql = file
+            .contents_utf8()
+            .with_context(|| format!("Migration {} is not valid UTF-8", name))?
+            .to_string();
+
+        let checksum = sha256_hex(sql.as_bytes());
+        let description = rest.trim_end_matches(".sql").replace('_', " ");
+
+        migrations.push(Migration {
+            version,
+            description,
+            sql,
+            checksum,
+        });
+    }
 
-    // Create migrations table
-    tx.execute(
+    migrations.sort_by_key(|m| m.version);
+    Ok(migrations)
+}
+
+// Lowercase hex SHA-256 of a migration's contents, used for tamper detection.
+fn sha256_hex(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn run_migrations(conn: &Connection) -> Result<()> {
+    // The bookkeeping table must exist before we can query applied versions.
+    conn.execute(
         "CREATE TABLE IF NOT EXISTS schema_migrations (
             version INTEGER PRIMARY KEY,
             applied_at INTEGER NOT NULL,
@@ -47,103 +208,74 @@ fn run_migrations(conn: &Connection) -> Result<()> {
         [],
     )?;
 
-    // Run core schema migrations
-    let core_sql = include_str!("../../../db/migrations/001_initial.sql");
-    tx.execute_batch(core_sql)?;
-
-    // Run text aggregation schema migrations
-    let text_sql = include_str!("../../../db/migrations/002_text_aggregation.sql");
-    tx.execute_batch(text_sql)?;
-
-    tx.commit()?;
-    info!("Database migrations completed successfully");
-    Ok(())
-}
+    // Load the checksums we have on record, keyed by version.
+    let applied: HashMap<i64, String> = {
+        let mut stmt = conn.prepare("SELECT version, checksum FROM schema_migrations")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+        })?;
+        let mut map = HashMap::new();
+        for row in rows {
+            let (version, checksum) = row?;
+            map.insert(version, checksum.unwrap_or_default());
+        }
+        map
+    };
 
-fn verify_schema(conn: &Connection) -> Result<()> {
-    // Verify core tables
-    let core_tables = [
-        "events",
-        "retention_policies",
-        "event_types",
-        "event_statistics",
-    ];
-
-    // Verify text aggregation tables
-    let text_tables = [
-        "text_captures",
-        "text_summaries",
-        "application_context",
-        "content_relationships",
-        "text_statistics"
-    ];
-
-    // Combined verification
-    for table in core_tables.iter().chain(text_tables.iter()) {
-        conn.query_row(
-            "SELECT 1 FROM sqlite_master WHERE type='table' AND name=?",
-            [table],
-            |_| Ok(()),
-        ).context(format!("Table '{}' not found", table))?;
-    }
+    let migrations = discover_migrations()?;
 
-    // Verify required indexes
-    let required_indexes = [
-        // Core indexes
-        "idx_events_timestamp",
-        "idx_events_source_type",
-        "idx_events_partition",
-        // Text capture indexes
-        "idx_text_captures_timestamp",
-        "idx_text_captures_app",
-        "idx_text_captures_type",
-        "idx_text_summaries_capture",
-        "idx_content_relationships_source",
-        "idx_content_relationships_target"
-    ];
-
-    for index in required_indexes.iter() {
-        conn.query_row(
-            "SELECT 1 FROM sqlite_master WHERE type='index' AND name=?",
-            [index],
-            |_| Ok(()),
-        ).context(format!("Required index '{}' not found", index))?;
-    }
+    for migration in &migrations {
+        if let Some(stored) = applied.get(&migration.version) {
+            // Already applied — refuse to continue if the file has since
+            // changed, since the live schema no longer matches the source.
+            if stored != &migration.checksum {
+                bail!(
+                    "Checksum mismatch for migration {:03} ({}): recorded {} but file on disk is {}. \
+                     A migration must never be edited after it has been applied.",
+                    migration.version,
+                    migration.description,
+                    stored,
+                    migration.checksum
+                );
+            }
+            continue;
+        }
 
-    // Verify views
-    let required_views = [
-        // Core views
-        "v_daily_activity",
-        // Text aggregation views
-        "v_text_activity_summary",
-        "v_application_usage",
-        "v_content_connections"
-    ];
-
-    for view in required_views.iter() {
-        conn.query_row(
-            "SELECT 1 FROM sqlite_master WHERE type='view' AND name=?",
-            [view],
-            |_| Ok(()),
-        ).context(format!("Required view '{}' not found", view))?;
-    }
+        // Apply the migration and record it atomically.
+        conn.execute_batch("BEGIN")?;
+        let apply = (|| -> Result<()> {
+            conn.execute_batch(&migration.sql).with_context(|| {
+                format!("Failed to apply migration {:03}", migration.version)
+            })?;
+            conn.execute(
+                "INSERT INTO schema_migrations (version, applied_at, description, checksum)
+                 VALUES (?, ?, ?, ?)",
+                params![
+                    migration.version,
+                    chrono::Utc::now().timestamp(),
+                    migration.description,
+                    migration.checksum,
+                ],
+            )?;
+            Ok(())
+        })();
 
-    // Verify text-specific triggers exist
-    let required_triggers = [
-        "trg_text_capture_stats",
-        "trg_summary_update",
-        "trg_relationship_cleanup"
-    ];
-
-    for trigger in required_triggers.iter() {
-        conn.query_row(
-            "SELECT 1 FROM sqlite_master WHERE type='trigger' AND name=?",
-            [trigger],
-            |_| Ok(()),
-        ).context(format!("Required trigger '{}' not found", trigger))?;
+        match apply {
+            Ok(()) => {
+                conn.execute_batch("COMMIT")?;
+                info!(
+                    "Applied migration {:03}: {}",
+                    migration.version, migration.description
+                );
+            }
+            Err(e) => {
+                conn.execute_batch("ROLLBACK").ok();
+                return Err(e);
+            }
+        }
     }
 
-    info!("Database schema verification completed successfully");
+    info!("Database migrations completed successfully");
     Ok(())
 }
 
@@ -211,4 +343,46 @@ mod tests {
         assert_eq!(count, 1);
         Ok(())
     }
+
+    #[test]
+    fn test_migrations_recorded_and_idempotent() -> Result<()> {
+        let dir = tempdir()?;
+        let db_path = dir.path().join("test.db");
+
+        // A fresh install records every discovered migration exactly once.
+        let conn = init_database(db_path.to_str().unwrap(), "test-key")?;
+        let applied: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM schema_migrations",
+            [],
+            |row| row.get(0),
+        )?;
+        assert_eq!(applied as usize, discover_migrations()?.len());
+        drop(conn);
+
+        // Re-opening applies nothing new and leaves the records untouched.
+        let conn = init_database(db_path.to_str().unwrap(), "test-key")?;
+        let applied_again: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM schema_migrations",
+            [],
+            |row| row.get(0),
+        )?;
+        assert_eq!(applied_again, applied);
+        Ok(())
+    }
+
+    #[test]
+    fn test_checksum_tamper_is_detected() -> Result<()> {
+        let dir = tempdir()?;
+        let db_path = dir.path().join("test.db");
+        let conn = init_database(db_path.to_str().unwrap(), "test-key")?;
+
+        // Corrupt a recorded checksum to simulate an edited-after-apply file.
+        conn.execute(
+            "UPDATE schema_migrations SET checksum = 'deadbeef' WHERE version = 1",
+            [],
+        )?;
+
+        assert!(run_migrations(&conn).is_err());
+        Ok(())
+    }
 }
\ No newline at end of file
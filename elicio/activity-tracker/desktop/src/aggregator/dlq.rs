@@ -0,0 +1,328 @@
+// Dead-letter queue for failed batch flushes.
+//
+// When `flush_batch`/`flush_text_batch` fail, the offending records are parked
+// in the `dead_letters` table instead of being dropped. A background drainer
+// re-attempts them with exponential backoff, bumping `attempts` each time;
+// once `attempts` exceeds `max_retries` the row is marked `permanent` and left
+// for inspection.
+//
+// A sliding window of flush failures guards the pipeline: if more than
+// `failure_limit` failures occur within `window_ms`, new submissions are
+// rejected until the window drains, so a runaway DB problem surfaces instead
+// of silently accumulating.
+use anyhow::{Context, Result};
+use rusqlite::params;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use super::db::Database;
+use super::metrics::MetricsHandle;
+use super::sink::ExportSink;
+use super::{Event, TextCapture};
+
+/// Tuning knobs, surfaced on `AggregatorConfig`.
+#[derive(Debug, Clone)]
+pub struct DlqConfig {
+    pub max_retries: u32,
+    pub failure_limit: usize,
+    pub window_ms: u64,
+}
+
+impl Default for DlqConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            failure_limit: 20,
+            window_ms: 60_000,
+        }
+    }
+}
+
+/// Records failed flushes and tracks the failure window used for backpressure.
+pub struct DeadLetterQueue {
+    db: Database,
+    config: DlqConfig,
+    metrics: MetricsHandle,
+    // Streaming export sink, shared with the flush path. Export failures are
+    // re-produced here on drain rather than re-inserted into the DB.
+    export: Option<Arc<dyn ExportSink>>,
+    // Timestamps (epoch ms) of recent flush failures, pruned to the window.
+    failures: Mutex<VecDeque<i64>>,
+}
+
+impl DeadLetterQueue {
+    pub fn new(
+        db: Database,
+        config: DlqConfig,
+        metrics: MetricsHandle,
+        export: Option<Arc<dyn ExportSink>>,
+    ) -> Self {
+        Self {
+            db,
+            config,
+            metrics,
+            export,
+            failures: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    // Refresh the `dlq_depth` gauge from the pending row count.
+    fn report_depth(&self, conn: &super::db::PooledConn) {
+        if let Ok(depth) = conn.query_row(
+            "SELECT COUNT(*) FROM dead_letters WHERE status = 'pending'",
+            [],
+            |row| row.get::<_, i64>(0),
+        ) {
+            self.metrics.set_dlq_depth(depth as u64);
+        }
+    }
+
+    /// Park a batch of failed events and register a failure in the window.
+    pub fn record_events(&self, events: &[Event], error: &str) {
+        self.record(
+            "event",
+            events.iter().map(|e| serde_json::to_string(e)),
+            error,
+        );
+    }
+
+    /// Park a batch of failed text captures and register a failure.
+    pub fn record_text_captures(&self, captures: &[TextCapture], error: &str) {
+        self.record(
+            "text_capture",
+            captures.iter().map(|c| serde_json::to_string(c)),
+            error,
+        );
+    }
+
+    /// Park events whose post-commit export failed. These rows are already in
+    /// the DB, so the drainer re-produces them to the export sink rather than
+    /// re-inserting them (which would duplicate the committed rows).
+    pub fn record_event_export(&self, events: &[Event], error: &str) {
+        self.record(
+            "event_export",
+            events.iter().map(|e| serde_json::to_string(e)),
+            error,
+        );
+    }
+
+    /// Park text captures whose post-commit export failed; re-produced, not
+    /// re-inserted, on drain.
+    pub fn record_text_capture_export(&self, captures: &[TextCapture], error: &str) {
+        self.record(
+            "text_capture_export",
+            captures.iter().map(|c| serde_json::to_string(c)),
+            error,
+        );
+    }
+
+    fn record<I>(&self, kind: &str, payloads: I, error: &str)
+    where
+        I: Iterator<Item = serde_json::Result<String>>,
+    {
+        self.register_failure();
+        let conn = match self.db.get() {
+            Ok(conn) => conn,
+            Err(e) => {
+                error!("Dead-letter store unreachable, dropping batch: {}", e);
+                return;
+            }
+        };
+        let now = now_ms() / 1000;
+        for payload in payloads {
+            match payload {
+                Ok(payload) => {
+                    if let Err(e) = conn.execute(
+                        "INSERT INTO dead_letters (kind, payload, error, first_seen_at, next_retry_at)
+                         VALUES (?, ?, ?, ?, ?)",
+                        params![kind, payload, error, now, now],
+                    ) {
+                        error!("Failed to dead-letter a {} record: {}", kind, e);
+                    }
+                }
+                Err(e) => error!("Failed to serialize dead-letter payload: {}", e),
+            }
+        }
+        self.report_depth(&conn);
+        warn!("Dead-lettered a failed {} flush: {}", kind, error);
+    }
+
+    /// Register a flush failure in the sliding window.
+    fn register_failure(&self) {
+        let now = now_ms();
+        let mut failures = self.failures.lock().unwrap();
+        failures.push_back(now);
+        self.prune(&mut failures, now);
+    }
+
+    fn prune(&self, failures: &mut VecDeque<i64>, now: i64) {
+        let cutoff = now - self.config.window_ms as i64;
+        while failures.front().is_some_and(|&t| t < cutoff) {
+            failures.pop_front();
+        }
+    }
+
+    /// Whether new submissions should currently be accepted. Returns false once
+    /// the failure window is saturated, so `submit_*` can surface an error.
+    pub fn accepting(&self) -> bool {
+        let now = now_ms();
+        let mut failures = self.failures.lock().unwrap();
+        self.prune(&mut failures, now);
+        failures.len() <= self.config.failure_limit
+    }
+
+    /// Periodically drain dead letters until cancelled. Spawned in
+    /// `Aggregator::new`; wakes on a fixed tick and only touches rows whose
+    /// per-row backoff gate (`next_retry_at`) has elapsed.
+    pub async fn run_drainer(self: std::sync::Arc<Self>, tick: Duration) {
+        let mut interval = tokio::time::interval(tick);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.drain_once().await {
+                warn!("Dead-letter drain pass failed: {}", e);
+            }
+        }
+    }
+
+    /// Drain due dead letters once, re-attempting each and arming an
+    /// exponential backoff gate (base delay doubling per attempt) on failure.
+    pub async fn drain_once(&self) -> Result<()> {
+        let now = now_ms() / 1000;
+
+        // Read the due rows into memory and drop the connection before any
+        // re-produce await, so no `rusqlite` handle is held across `.await`.
+        let rows = {
+            let conn = self.db.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT id, kind, payload, attempts FROM dead_letters
+                 WHERE status = 'pending' AND next_retry_at <= ?
+                 ORDER BY id",
+            )?;
+            stmt.query_map(params![now], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, u32>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+        };
+
+        for (id, kind, payload, attempts) in rows {
+            let outcome = self.reattempt(&kind, &payload).await;
+            let conn = self.db.get()?;
+            match outcome {
+                Ok(()) => {
+                    conn.execute("DELETE FROM dead_letters WHERE id = ?", params![id])?;
+                    info!("Recovered dead-lettered {} record {}", kind, id);
+                }
+                Err(e) => {
+                    let next = attempts + 1;
+                    if next > self.config.max_retries {
+                        conn.execute(
+                            "UPDATE dead_letters SET attempts = ?, error = ?, status = 'permanent'
+                             WHERE id = ?",
+                            params![next, e.to_string(), id],
+                        )?;
+                        warn!("Dead letter {} exhausted retries, marked permanent", id);
+                    } else {
+                        let retry_at = now + self.backoff(next).as_secs() as i64;
+                        conn.execute(
+                            "UPDATE dead_letters SET attempts = ?, error = ?, next_retry_at = ?
+                             WHERE id = ?",
+                            params![next, e.to_string(), retry_at, id],
+                        )?;
+                    }
+                }
+            }
+        }
+        let conn = self.db.get()?;
+        self.report_depth(&conn);
+        Ok(())
+    }
+
+    // Backoff delay before a dead letter is eligible again, doubling per attempt.
+    fn backoff(&self, attempts: u32) -> Duration {
+        let base = 1u64;
+        Duration::from_secs(base.saturating_mul(1 << attempts.min(10)))
+    }
+
+    // Re-attempt a single parked record. Flush failures (`event`/`text_capture`)
+    // are re-inserted into their destination table; export failures
+    // (`*_export`) are re-produced to the export sink, since those rows are
+    // already committed to the DB.
+    async fn reattempt(&self, kind: &str, payload: &str) -> Result<()> {
+        match kind {
+            "event" => self.reinsert_event(payload),
+            "text_capture" => self.reinsert_text_capture(payload),
+            "event_export" => {
+                let event: Event =
+                    serde_json::from_str(payload).context("Malformed dead-lettered event")?;
+                self.export_sink()?.export_events(&[event]).await
+            }
+            "text_capture_export" => {
+                let capture: TextCapture =
+                    serde_json::from_str(payload).context("Malformed dead-lettered capture")?;
+                self.export_sink()?.export_text_captures(&[capture]).await
+            }
+            other => anyhow::bail!("Unknown dead-letter kind: {}", other),
+        }
+    }
+
+    // The export sink, or an error so the row stays pending for a later retry
+    // when export was not configured (or was torn down) at drain time.
+    fn export_sink(&self) -> Result<&Arc<dyn ExportSink>> {
+        self.export
+            .as_ref()
+            .context("export failure parked but no export sink is configured")
+    }
+
+    fn reinsert_event(&self, payload: &str) -> Result<()> {
+        let event: Event =
+            serde_json::from_str(payload).context("Malformed dead-lettered event")?;
+        let conn = self.db.get()?;
+        conn.execute(
+            "INSERT INTO events (timestamp, source, event_type, metadata, inserted_at, partition_key)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                event.timestamp,
+                event.source,
+                event.event_type,
+                event.metadata.to_string(),
+                now_ms() / 1000,
+                event.partition_key,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn reinsert_text_capture(&self, payload: &str) -> Result<()> {
+        let capture: TextCapture =
+            serde_json::from_str(payload).context("Malformed dead-lettered capture")?;
+        let conn = self.db.get()?;
+        conn.execute(
+            "INSERT INTO text_captures (
+                text, app_name, window_title, timestamp,
+                text_type, context, summary, partition_key
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                capture.text,
+                capture.app_name,
+                capture.window_title,
+                capture.timestamp,
+                serde_json::to_string(&capture.text_type)?,
+                serde_json::to_string(&capture.context)?,
+                None::<String>,
+                capture.partition_key,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
@@ -0,0 +1,459 @@
+// Columnar export subsystem.
+//
+// Dumps the `events`, `text_captures` and `health_metrics` tables into Parquet
+// files — one file per `partition_key` per table — so users can query their
+// history in DuckDB/Polars without decrypting through the crate.
+//
+// The low-cardinality string columns (`source`, `event_type`, `app_name`,
+// `window_title`, `text_type`) are dictionary-encoded: each column keeps a
+// dictionary of distinct values and stores small integer indices, which shrinks
+// these highly repetitive columns dramatically. High-cardinality `metadata`
+// JSON is kept as plain UTF-8. Rows are streamed in batches so a multi-GB
+// history never has to be materialised in memory.
+use anyhow::{Context, Result};
+use arrow::array::{Array, ArrayRef, Float64Array, Int32DictionaryArray, Int64Array, StringBuilder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::info;
+
+use super::db::Database;
+
+/// How many rows to pull from SQLite and write to Parquet per batch.
+const BATCH_ROWS: usize = 8_192;
+
+/// One exported file plus the time range it covers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedFile {
+    pub table: String,
+    pub partition_key: String,
+    pub path: String,
+    pub row_count: i64,
+    pub min_timestamp: i64,
+    pub max_timestamp: i64,
+}
+
+/// Manifest of everything produced by an export run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub files: Vec<ExportedFile>,
+}
+
+/// Exports the store to Parquet under `out_dir`, returning a manifest.
+pub struct ParquetExporter {
+    out_dir: PathBuf,
+    compression: Compression,
+}
+
+impl ParquetExporter {
+    pub fn new(out_dir: impl AsRef<Path>) -> Self {
+        Self {
+            out_dir: out_dir.as_ref().to_path_buf(),
+            compression: Compression::SNAPPY,
+        }
+    }
+
+    /// Export every partition of the `events`, `text_captures` and
+    /// `health_metrics` tables.
+    pub fn export(&self, db: &Database) -> Result<ExportManifest> {
+        std::fs::create_dir_all(&self.out_dir)
+            .with_context(|| format!("Failed to create export dir {:?}", self.out_dir))?;
+
+        let mut manifest = ExportManifest::default();
+        for partition in self.partitions(db, "events")? {
+            if let Some(file) = self.export_events(db, &partition)? {
+                manifest.files.push(file);
+            }
+        }
+        for partition in self.partitions(db, "text_captures")? {
+            if let Some(file) = self.export_text_captures(db, &partition)? {
+                manifest.files.push(file);
+            }
+        }
+        // `health_metrics` carries no `partition_key` column — it hangs off an
+        // event row — so partitions are derived from `start_time`, month-
+        // granular, to match the `YYYY_MM` keys the other tables use.
+        for partition in self.health_metric_partitions(db)? {
+            if let Some(file) = self.export_health_metrics(db, &partition)? {
+                manifest.files.push(file);
+            }
+        }
+
+        self.write_manifest(&manifest)?;
+        info!("Exported {} Parquet file(s)", manifest.files.len());
+        Ok(manifest)
+    }
+
+    // Distinct partition keys for a table, so we emit one file per partition.
+    fn partitions(&self, db: &Database, table: &str) -> Result<Vec<String>> {
+        let conn = db.get()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT DISTINCT partition_key FROM {} WHERE partition_key IS NOT NULL \
+             ORDER BY partition_key",
+            table
+        ))?;
+        let keys = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(keys)
+    }
+
+    fn export_events(&self, db: &Database, partition: &str) -> Result<Option<ExportedFile>> {
+        // source/event_type are dictionary-encoded; metadata stays plain UTF-8.
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("timestamp", DataType::Int64, false),
+            dict_field("source"),
+            dict_field("event_type"),
+            Field::new("metadata", DataType::Utf8, true),
+        ]));
+
+        let conn = db.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, source, event_type, metadata
+             FROM events WHERE partition_key = ? ORDER BY timestamp",
+        )?;
+        let mut rows = stmt.query([partition])?;
+
+        let path = self.out_dir.join(format!("events_{}.parquet", partition));
+        let mut sink = BatchSink::new(&path, schema.clone(), self.compression)?;
+        let mut ts = TimeRange::default();
+
+        let mut timestamps = Vec::with_capacity(BATCH_ROWS);
+        let mut sources = StringBuilder::new();
+        let mut event_types = StringBuilder::new();
+        let mut metadata = StringBuilder::new();
+
+        while let Some(row) = rows.next()? {
+            let timestamp: i64 = row.get(0)?;
+            ts.observe(timestamp);
+            timestamps.push(timestamp);
+            sources.append_value(row.get::<_, String>(1)?);
+            event_types.append_value(row.get::<_, String>(2)?);
+            metadata.append_option(row.get::<_, Option<String>>(3)?);
+
+            if timestamps.len() >= BATCH_ROWS {
+                let batch = RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(Int64Array::from(std::mem::take(&mut timestamps))) as ArrayRef,
+                        dict_array(&mut sources),
+                        dict_array(&mut event_types),
+                        Arc::new(metadata.finish()) as ArrayRef,
+                    ],
+                )?;
+                sink.write(&batch)?;
+            }
+        }
+
+        if !timestamps.is_empty() {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from(timestamps)) as ArrayRef,
+                    dict_array(&mut sources),
+                    dict_array(&mut event_types),
+                    Arc::new(metadata.finish()) as ArrayRef,
+                ],
+            )?;
+            sink.write(&batch)?;
+        }
+
+        let row_count = sink.finish()?;
+        if row_count == 0 {
+            std::fs::remove_file(&path).ok();
+            return Ok(None);
+        }
+
+        Ok(Some(ExportedFile {
+            table: "events".to_string(),
+            partition_key: partition.to_string(),
+            path: path.to_string_lossy().into_owned(),
+            row_count,
+            min_timestamp: ts.min,
+            max_timestamp: ts.max,
+        }))
+    }
+
+    fn export_text_captures(&self, db: &Database, partition: &str) -> Result<Option<ExportedFile>> {
+        // app_name/window_title/text_type are dictionary-encoded; the captured
+        // text stays plain UTF-8.
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("timestamp", DataType::Int64, false),
+            dict_field("app_name"),
+            dict_field("window_title"),
+            dict_field("text_type"),
+            Field::new("text", DataType::Utf8, false),
+        ]));
+
+        let conn = db.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, app_name, window_title, text_type, text
+             FROM text_captures WHERE partition_key = ? ORDER BY timestamp",
+        )?;
+        let mut rows = stmt.query([partition])?;
+
+        let path = self
+            .out_dir
+            .join(format!("text_captures_{}.parquet", partition));
+        let mut sink = BatchSink::new(&path, schema.clone(), self.compression)?;
+        let mut ts = TimeRange::default();
+
+        let mut timestamps = Vec::with_capacity(BATCH_ROWS);
+        let mut app_names = StringBuilder::new();
+        let mut window_titles = StringBuilder::new();
+        let mut text_types = StringBuilder::new();
+        let mut texts = StringBuilder::new();
+
+        while let Some(row) = rows.next()? {
+            let timestamp: i64 = row.get(0)?;
+            ts.observe(timestamp);
+            timestamps.push(timestamp);
+            app_names.append_value(row.get::<_, String>(1)?);
+            window_titles.append_value(row.get::<_, String>(2)?);
+            text_types.append_value(row.get::<_, String>(3)?);
+            texts.append_value(row.get::<_, String>(4)?);
+
+            if timestamps.len() >= BATCH_ROWS {
+                let batch = RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(Int64Array::from(std::mem::take(&mut timestamps))) as ArrayRef,
+                        dict_array(&mut app_names),
+                        dict_array(&mut window_titles),
+                        dict_array(&mut text_types),
+                        Arc::new(texts.finish()) as ArrayRef,
+                    ],
+                )?;
+                sink.write(&batch)?;
+            }
+        }
+
+        if !timestamps.is_empty() {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from(timestamps)) as ArrayRef,
+                    dict_array(&mut app_names),
+                    dict_array(&mut window_titles),
+                    dict_array(&mut text_types),
+                    Arc::new(texts.finish()) as ArrayRef,
+                ],
+            )?;
+            sink.write(&batch)?;
+        }
+
+        let row_count = sink.finish()?;
+        if row_count == 0 {
+            std::fs::remove_file(&path).ok();
+            return Ok(None);
+        }
+
+        Ok(Some(ExportedFile {
+            table: "text_captures".to_string(),
+            partition_key: partition.to_string(),
+            path: path.to_string_lossy().into_owned(),
+            row_count,
+            min_timestamp: ts.min,
+            max_timestamp: ts.max,
+        }))
+    }
+
+    // Month-granular partitions (`YYYY_MM`) derived from `start_time`, since
+    // `health_metrics` has no `partition_key` of its own.
+    fn health_metric_partitions(&self, db: &Database) -> Result<Vec<String>> {
+        let conn = db.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT strftime('%Y_%m', start_time / 1000, 'unixepoch') AS partition \
+             FROM health_metrics WHERE start_time IS NOT NULL ORDER BY partition",
+        )?;
+        let keys = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(keys)
+    }
+
+    fn export_health_metrics(&self, db: &Database, partition: &str) -> Result<Option<ExportedFile>> {
+        // metric_type/unit are dictionary-encoded; the numeric columns and the
+        // optional device/metadata stay plain.
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("start_time", DataType::Int64, false),
+            dict_field("metric_type"),
+            dict_field("unit"),
+            Field::new("value", DataType::Float64, false),
+            Field::new("end_time", DataType::Int64, false),
+            Field::new("source_device", DataType::Utf8, true),
+            Field::new("metadata", DataType::Utf8, true),
+        ]));
+
+        let conn = db.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT start_time, metric_type, unit, value, end_time, source_device, metadata
+             FROM health_metrics
+             WHERE strftime('%Y_%m', start_time / 1000, 'unixepoch') = ?
+             ORDER BY start_time",
+        )?;
+        let mut rows = stmt.query([partition])?;
+
+        let path = self
+            .out_dir
+            .join(format!("health_metrics_{}.parquet", partition));
+        let mut sink = BatchSink::new(&path, schema.clone(), self.compression)?;
+        let mut ts = TimeRange::default();
+
+        let mut start_times = Vec::with_capacity(BATCH_ROWS);
+        let mut metric_types = StringBuilder::new();
+        let mut units = StringBuilder::new();
+        let mut values = Vec::with_capacity(BATCH_ROWS);
+        let mut end_times = Vec::with_capacity(BATCH_ROWS);
+        let mut source_devices = StringBuilder::new();
+        let mut metadata = StringBuilder::new();
+
+        while let Some(row) = rows.next()? {
+            let start_time: i64 = row.get(0)?;
+            ts.observe(start_time);
+            start_times.push(start_time);
+            metric_types.append_value(row.get::<_, String>(1)?);
+            units.append_value(row.get::<_, String>(2)?);
+            values.push(row.get::<_, f64>(3)?);
+            end_times.push(row.get::<_, i64>(4)?);
+            source_devices.append_option(row.get::<_, Option<String>>(5)?);
+            metadata.append_option(row.get::<_, Option<String>>(6)?);
+
+            if start_times.len() >= BATCH_ROWS {
+                let batch = RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(Int64Array::from(std::mem::take(&mut start_times))) as ArrayRef,
+                        dict_array(&mut metric_types),
+                        dict_array(&mut units),
+                        Arc::new(Float64Array::from(std::mem::take(&mut values))) as ArrayRef,
+                        Arc::new(Int64Array::from(std::mem::take(&mut end_times))) as ArrayRef,
+                        Arc::new(source_devices.finish()) as ArrayRef,
+                        Arc::new(metadata.finish()) as ArrayRef,
+                    ],
+                )?;
+                sink.write(&batch)?;
+            }
+        }
+
+        if !start_times.is_empty() {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from(start_times)) as ArrayRef,
+                    dict_array(&mut metric_types),
+                    dict_array(&mut units),
+                    Arc::new(Float64Array::from(values)) as ArrayRef,
+                    Arc::new(Int64Array::from(end_times)) as ArrayRef,
+                    Arc::new(source_devices.finish()) as ArrayRef,
+                    Arc::new(metadata.finish()) as ArrayRef,
+                ],
+            )?;
+            sink.write(&batch)?;
+        }
+
+        let row_count = sink.finish()?;
+        if row_count == 0 {
+            std::fs::remove_file(&path).ok();
+            return Ok(None);
+        }
+
+        Ok(Some(ExportedFile {
+            table: "health_metrics".to_string(),
+            partition_key: partition.to_string(),
+            path: path.to_string_lossy().into_owned(),
+            row_count,
+            min_timestamp: ts.min,
+            max_timestamp: ts.max,
+        }))
+    }
+
+    fn write_manifest(&self, manifest: &ExportManifest) -> Result<()> {
+        let path = self.out_dir.join("manifest.json");
+        let json = serde_json::to_string_pretty(manifest)?;
+        std::fs::write(&path, json)
+            .with_context(|| format!("Failed to write manifest {:?}", path))?;
+        Ok(())
+    }
+}
+
+// A dictionary-encoded Utf8 column (Int32 indices -> Utf8 values).
+fn dict_field(name: &str) -> Field {
+    Field::new(
+        name,
+        DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+        false,
+    )
+}
+
+// Build a dictionary array from the accumulated values, resetting the builder.
+fn dict_array(builder: &mut StringBuilder) -> ArrayRef {
+    let values = builder.finish();
+    let dict: Int32DictionaryArray = (0..values.len()).map(|i| values.value(i)).collect();
+    Arc::new(dict) as ArrayRef
+}
+
+// Tracks the timestamp range seen while streaming a table.
+struct TimeRange {
+    min: i64,
+    max: i64,
+    seen: bool,
+}
+
+impl Default for TimeRange {
+    fn default() -> Self {
+        Self {
+            min: 0,
+            max: 0,
+            seen: false,
+        }
+    }
+}
+
+impl TimeRange {
+    fn observe(&mut self, ts: i64) {
+        if !self.seen {
+            self.min = ts;
+            self.max = ts;
+            self.seen = true;
+        } else {
+            self.min = self.min.min(ts);
+            self.max = self.max.max(ts);
+        }
+    }
+}
+
+// Streams record batches into a single Parquet file, tracking row count.
+struct BatchSink {
+    writer: ArrowWriter<File>,
+    rows: i64,
+}
+
+impl BatchSink {
+    fn new(path: &Path, schema: Arc<Schema>, compression: Compression) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create {:?}", path))?;
+        let props = WriterProperties::builder()
+            .set_compression(compression)
+            .build();
+        let writer = ArrowWriter::try_new(file, schema, Some(props))?;
+        Ok(Self { writer, rows: 0 })
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.rows += batch.num_rows() as i64;
+        self.writer.write(batch)?;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<i64> {
+        self.writer.close()?;
+        Ok(self.rows)
+    }
+}
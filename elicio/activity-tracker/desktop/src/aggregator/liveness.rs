@@ -0,0 +1,227 @@
+// Operational liveness probing for the pipeline itself.
+//
+// The `health` module records health *metrics* (heart rate, sleep, ...); this
+// one answers "is the aggregator working?" — the readiness signal an embedding
+// service exposes on `/healthz`. A periodic task samples each subsystem and
+// logs a warning whenever one degrades, and `Aggregator::health_status`
+// returns the same structured per-component report on demand.
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Instant;
+
+use serde::Serialize;
+use tracing::warn;
+
+use super::db::Database;
+
+/// Tuning for the liveness checks.
+#[derive(Debug, Clone)]
+pub struct LivenessConfig {
+    /// How often the background probe runs.
+    pub check_interval_ms: u64,
+    /// A DB ping slower than this counts as degraded.
+    pub db_timeout_ms: u64,
+    /// A processor that has neither flushed nor idled within this window is
+    /// considered stalled.
+    pub flush_staleness_ms: u64,
+    /// Channel usage (queued / capacity) above this fraction is degraded.
+    pub channel_usage_threshold: f64,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_ms: 15_000,
+            db_timeout_ms: 500,
+            flush_staleness_ms: 30_000,
+            channel_usage_threshold: 0.8,
+        }
+    }
+}
+
+/// Per-component status, coarsened to the three states a readiness probe needs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum ComponentStatus {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+/// A single subsystem's status plus a human reason when it is not healthy.
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentHealth {
+    pub name: String,
+    pub status: ComponentStatus,
+    pub reason: Option<String>,
+}
+
+/// Aggregate readiness across every probed component.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub overall: ComponentStatus,
+    pub components: Vec<ComponentHealth>,
+}
+
+/// Last-activity timestamps the batch processors stamp so the probe can tell a
+/// stalled pipeline from an idle one. Shared via `Arc`.
+pub struct Liveness {
+    event_last_active: AtomicI64,
+    text_last_active: AtomicI64,
+}
+
+impl Liveness {
+    pub fn new() -> Self {
+        let now = now_ms();
+        Self {
+            event_last_active: AtomicI64::new(now),
+            text_last_active: AtomicI64::new(now),
+        }
+    }
+
+    /// Stamp the event processor as having flushed or idled cleanly.
+    pub fn mark_event_active(&self) {
+        self.event_last_active.store(now_ms(), Ordering::Relaxed);
+    }
+
+    /// Stamp the text processor as having flushed or idled cleanly.
+    pub fn mark_text_active(&self) {
+        self.text_last_active.store(now_ms(), Ordering::Relaxed);
+    }
+
+    fn event_idle_ms(&self) -> i64 {
+        now_ms() - self.event_last_active.load(Ordering::Relaxed)
+    }
+
+    fn text_idle_ms(&self) -> i64 {
+        now_ms() - self.text_last_active.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for Liveness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Inputs the probe reads that live outside this module.
+pub(crate) struct Probe<'a> {
+    pub db: &'a Database,
+    pub liveness: &'a Liveness,
+    pub config: &'a LivenessConfig,
+    /// Event channel usage as queued/capacity in `0.0..=1.0`.
+    pub event_channel_usage: f64,
+    /// Text channel usage as queued/capacity in `0.0..=1.0`.
+    pub text_channel_usage: f64,
+    /// Whether the summarization model loaded successfully at startup.
+    pub model_loaded: bool,
+}
+
+/// Sample every component and assemble a report. The overall status is the
+/// worst component status (Unhealthy > Degraded > Healthy).
+pub(crate) fn evaluate(probe: &Probe<'_>) -> HealthReport {
+    let mut components = Vec::new();
+    components.push(check_database(probe));
+    components.push(check_processor("event_processor", probe.liveness.event_idle_ms(), probe.config));
+    components.push(check_processor("text_processor", probe.liveness.text_idle_ms(), probe.config));
+    components.push(check_channel("event_channel", probe.event_channel_usage, probe.config));
+    components.push(check_channel("text_channel", probe.text_channel_usage, probe.config));
+    components.push(check_model(probe.model_loaded));
+
+    let overall = components
+        .iter()
+        .map(|c| c.status.clone())
+        .max_by_key(severity)
+        .unwrap_or(ComponentStatus::Healthy);
+
+    HealthReport { overall, components }
+}
+
+fn check_database(probe: &Probe<'_>) -> ComponentHealth {
+    let started = Instant::now();
+    match probe.db.get().and_then(|conn| {
+        conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))
+            .map_err(Into::into)
+    }) {
+        Err(e) => degraded_or_unhealthy("database", ComponentStatus::Unhealthy, format!("DB ping failed: {e}")),
+        Ok(_) => {
+            let elapsed = started.elapsed().as_millis() as u64;
+            if elapsed > probe.config.db_timeout_ms {
+                degraded_or_unhealthy(
+                    "database",
+                    ComponentStatus::Degraded,
+                    format!("DB ping took {elapsed}ms (> {}ms)", probe.config.db_timeout_ms),
+                )
+            } else {
+                healthy("database")
+            }
+        }
+    }
+}
+
+fn check_processor(name: &str, idle_ms: i64, config: &LivenessConfig) -> ComponentHealth {
+    if idle_ms as u64 > config.flush_staleness_ms {
+        degraded_or_unhealthy(
+            name,
+            ComponentStatus::Degraded,
+            format!("no flush or idle tick for {idle_ms}ms (> {}ms)", config.flush_staleness_ms),
+        )
+    } else {
+        healthy(name)
+    }
+}
+
+fn check_channel(name: &str, usage: f64, config: &LivenessConfig) -> ComponentHealth {
+    if usage > config.channel_usage_threshold {
+        degraded_or_unhealthy(
+            name,
+            ComponentStatus::Degraded,
+            format!("backlog at {:.0}% (> {:.0}%)", usage * 100.0, config.channel_usage_threshold * 100.0),
+        )
+    } else {
+        healthy(name)
+    }
+}
+
+fn check_model(loaded: bool) -> ComponentHealth {
+    if loaded {
+        healthy("summarization_model")
+    } else {
+        // A missing model is non-fatal: captures are still stored verbatim, so
+        // this degrades health rather than failing the whole `/healthz` probe.
+        // `Unhealthy` is reserved for conditions that actually stall the pipeline.
+        degraded_or_unhealthy(
+            "summarization_model",
+            ComponentStatus::Degraded,
+            "model failed to load".to_string(),
+        )
+    }
+}
+
+fn severity(status: &ComponentStatus) -> u8 {
+    match status {
+        ComponentStatus::Healthy => 0,
+        ComponentStatus::Degraded => 1,
+        ComponentStatus::Unhealthy => 2,
+    }
+}
+
+fn healthy(name: &str) -> ComponentHealth {
+    ComponentHealth {
+        name: name.to_string(),
+        status: ComponentStatus::Healthy,
+        reason: None,
+    }
+}
+
+// Build a non-healthy component and log the degradation for operators.
+fn degraded_or_unhealthy(name: &str, status: ComponentStatus, reason: String) -> ComponentHealth {
+    warn!("Liveness: {} is {:?}: {}", name, status, reason);
+    ComponentHealth {
+        name: name.to_string(),
+        status,
+        reason: Some(reason),
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
@@ -0,0 +1,194 @@
+// Lightweight in-process metrics for the batch pipelines.
+//
+// Unlike the OpenTelemetry `telemetry` module (spans + a Prometheus scrape
+// endpoint), this is a dependency-free counter/gauge set built on atomics.
+// Increments are a single relaxed atomic op on the hot path; a background
+// emitter reads a snapshot on a fixed interval and ships it to a StatsD-style
+// UDP sink, so no syscall happens per event. Embedders that would rather pull
+// than push can call `Aggregator::metrics_handle()` and read `snapshot()`.
+use std::net::UdpSocket;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{debug, warn};
+
+/// Runtime configuration for the metrics subsystem.
+#[derive(Debug, Clone)]
+pub struct MetricsConfig {
+    /// Emit a snapshot to the sink this often.
+    pub flush_interval_ms: u64,
+    /// `host:port` of the StatsD-style UDP sink.
+    pub sink_addr: String,
+    /// When false, counters still update but nothing is emitted.
+    pub enabled: bool,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            flush_interval_ms: 10_000,
+            sink_addr: String::from("127.0.0.1:8125"),
+            enabled: false,
+        }
+    }
+}
+
+/// Shared handle to the live counters. Cloning is cheap (an `Arc` bump).
+pub type MetricsHandle = Arc<Metrics>;
+
+/// Process-wide counters and gauges for the two batch pipelines.
+#[derive(Default)]
+pub struct Metrics {
+    events_received: AtomicU64,
+    text_captures_received: AtomicU64,
+    batches_flushed: AtomicU64,
+    flush_errors: AtomicU64,
+    dlq_depth: AtomicU64,
+    summarizations_run: AtomicU64,
+    summarization_latency_ms: AtomicU64,
+    current_batch_size: AtomicU64,
+}
+
+/// A point-in-time read of every instrument, for pull-based scraping.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    pub events_received: u64,
+    pub text_captures_received: u64,
+    pub batches_flushed: u64,
+    pub flush_errors: u64,
+    pub dlq_depth: u64,
+    pub summarizations_run: u64,
+    pub summarization_latency_ms: u64,
+    pub current_batch_size: u64,
+}
+
+impl Metrics {
+    pub fn new() -> MetricsHandle {
+        Arc::new(Metrics::default())
+    }
+
+    pub fn incr_events_received(&self, n: u64) {
+        self.events_received.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn incr_text_captures_received(&self, n: u64) {
+        self.text_captures_received.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn incr_batches_flushed(&self) {
+        self.batches_flushed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn incr_flush_errors(&self) {
+        self.flush_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn set_dlq_depth(&self, depth: u64) {
+        self.dlq_depth.store(depth, Ordering::Relaxed);
+    }
+
+    pub fn incr_summarizations_run(&self) {
+        self.summarizations_run.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn observe_summarization_latency_ms(&self, ms: u64) {
+        self.summarization_latency_ms.store(ms, Ordering::Relaxed);
+    }
+
+    pub fn set_current_batch_size(&self, size: u64) {
+        self.current_batch_size.store(size, Ordering::Relaxed);
+    }
+
+    /// Read every instrument at once.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            events_received: self.events_received.load(Ordering::Relaxed),
+            text_captures_received: self.text_captures_received.load(Ordering::Relaxed),
+            batches_flushed: self.batches_flushed.load(Ordering::Relaxed),
+            flush_errors: self.flush_errors.load(Ordering::Relaxed),
+            dlq_depth: self.dlq_depth.load(Ordering::Relaxed),
+            summarizations_run: self.summarizations_run.load(Ordering::Relaxed),
+            summarization_latency_ms: self.summarization_latency_ms.load(Ordering::Relaxed),
+            current_batch_size: self.current_batch_size.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl MetricsSnapshot {
+    /// Render as StatsD lines (`name:value|<type>`): monotonic counters as
+    /// `c`, point-in-time gauges as `g`.
+    fn to_statsd(&self) -> Vec<String> {
+        vec![
+            format!("aggregator.events_received:{}|c", self.events_received),
+            format!(
+                "aggregator.text_captures_received:{}|c",
+                self.text_captures_received
+            ),
+            format!("aggregator.batches_flushed:{}|c", self.batches_flushed),
+            format!("aggregator.flush_errors:{}|c", self.flush_errors),
+            format!("aggregator.dlq_depth:{}|g", self.dlq_depth),
+            format!(
+                "aggregator.summarizations_run:{}|c",
+                self.summarizations_run
+            ),
+            format!(
+                "aggregator.summarization_latency_ms:{}|g",
+                self.summarization_latency_ms
+            ),
+            format!(
+                "aggregator.current_batch_size:{}|g",
+                self.current_batch_size
+            ),
+        ]
+    }
+}
+
+/// Destination for a flushed metrics snapshot. The UDP StatsD sink is the
+/// default; embedders can supply their own (e.g. a log or HTTP sink).
+pub trait MetricsSink: Send + Sync {
+    fn emit(&self, lines: &[String]);
+}
+
+/// StatsD over UDP: one datagram per flush, fire-and-forget.
+pub struct StatsdSink {
+    socket: UdpSocket,
+    addr: String,
+}
+
+impl StatsdSink {
+    pub fn connect(addr: &str) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        Ok(Self {
+            socket,
+            addr: addr.to_string(),
+        })
+    }
+}
+
+impl MetricsSink for StatsdSink {
+    fn emit(&self, lines: &[String]) {
+        let payload = lines.join("\n");
+        if let Err(e) = self.socket.send_to(payload.as_bytes(), &self.addr) {
+            warn!("Failed to emit metrics to {}: {}", self.addr, e);
+        }
+    }
+}
+
+/// Spawn the buffered emitter: every `flush_interval_ms` it reads a snapshot
+/// and hands it to `sink`. A no-op when `config.enabled` is false.
+pub fn spawn_emitter(metrics: MetricsHandle, config: MetricsConfig, sink: Arc<dyn MetricsSink>) {
+    if !config.enabled {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(Duration::from_millis(config.flush_interval_ms));
+        loop {
+            interval.tick().await;
+            let lines = metrics.snapshot().to_statsd();
+            debug!("Emitting {} metric lines", lines.len());
+            sink.emit(&lines);
+        }
+    });
+}
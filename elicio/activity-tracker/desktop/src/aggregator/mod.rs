@@ -2,7 +2,7 @@
 // Provides unified handling of events and text captures with efficient batch processing
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
@@ -11,12 +11,30 @@ use uuid::Uuid;
 use rust_bert::pipelines::summarization::{SummarizationModel, SummarizationConfig};
 
 // Module declarations
+mod capture_filter;
 mod db;
+mod dlq;
+mod export;
 mod health;
+mod liveness;
+mod metrics;
+mod query;
 mod retention;
 mod schema;
+mod sink;
+mod storage;
+mod telemetry;
 mod text_capture;
 
+pub use capture_filter::FilterConfig;
+pub use liveness::{ComponentHealth, ComponentStatus, HealthReport, LivenessConfig};
+pub use metrics::{MetricsConfig, MetricsHandle, MetricsSink, MetricsSnapshot};
+pub use query::Broadcaster;
+pub use sink::{ExportConfig, ExportSink};
+pub use export::{ExportManifest, ExportedFile, ParquetExporter};
+pub use storage::{DailyActivity, StorageBackend, StorageConfig, StorageEngine, TextCaptureRecord};
+pub use telemetry::TelemetryConfig;
+
 // Core data models
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
@@ -40,6 +58,18 @@ pub struct TextCapture {
     pub partition_key: Option<String>,
 }
 
+// Live-subscription payload for a submitted health metric. Mirrors the
+// `health_metrics` columns a dashboard tails; the full row (source device,
+// accuracy, raw metadata) stays in the database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthMetric {
+    pub metric_type: String,
+    pub value: f64,
+    pub unit: String,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TextType {
     Input,    // User-generated content (typing, pasting)
@@ -60,8 +90,25 @@ pub struct CaptureContext {
 pub struct AggregatorConfig {
     pub db_path: String,
     pub encryption_key: String,
+    /// Storage engine backing the aggregator (SQLite by default).
+    pub engine: StorageEngine,
+    /// Postgres DSN, used only when `engine` is Postgres.
+    pub postgres_url: Option<String>,
     pub batch_size: usize,
     pub batch_interval_ms: u64,
+    /// Max re-attempts before a dead letter is marked permanent.
+    pub dlq_max_retries: u32,
+    /// Flush failures allowed within dlq_window_ms before submissions are rejected.
+    pub dlq_failure_limit: usize,
+    /// Sliding window (ms) over which dlq_failure_limit is counted.
+    pub dlq_window_ms: u64,
+    pub pool: db::PoolConfig,
+    pub telemetry: TelemetryConfig,
+    pub metrics: MetricsConfig,
+    pub liveness: LivenessConfig,
+    /// Optional streaming export; when set, committed records are also
+    /// produced to the configured bus.
+    pub export: Option<ExportConfig>,
     pub text_capture_config: TextCaptureConfig,
 }
 
@@ -71,6 +118,20 @@ pub struct TextCaptureConfig {
     pub summarization_threshold: usize,  // Length at which to generate summaries
     pub model_path: String,             // Path to the LLM model
     pub enable_accessibility: bool,      // Whether to enable OS-level text capture
+    pub filter: FilterConfig,           // App filtering + secret redaction rules
+}
+
+impl AggregatorConfig {
+    // Derive the storage-layer config selecting the backend engine.
+    pub fn storage_config(&self) -> StorageConfig {
+        StorageConfig {
+            engine: self.engine.clone(),
+            db_path: self.db_path.clone(),
+            encryption_key: self.encryption_key.clone(),
+            pool: self.pool.clone(),
+            postgres_url: self.postgres_url.clone(),
+        }
+    }
 }
 
 impl Default for AggregatorConfig {
@@ -78,8 +139,18 @@ impl Default for AggregatorConfig {
         Self {
             db_path: String::from("activity.db"),
             encryption_key: String::from("default-key"),
+            engine: StorageEngine::default(),
+            postgres_url: None,
             batch_size: 100,
             batch_interval_ms: 5000,
+            dlq_max_retries: 5,
+            dlq_failure_limit: 20,
+            dlq_window_ms: 60_000,
+            pool: db::PoolConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            metrics: MetricsConfig::default(),
+            liveness: LivenessConfig::default(),
+            export: None,
             text_capture_config: TextCaptureConfig::default(),
         }
     }
@@ -92,6 +163,7 @@ impl Default for TextCaptureConfig {
             summarization_threshold: 1000,
             model_path: String::from("models/bart-large-cnn"),
             enable_accessibility: true,
+            filter: FilterConfig::default(),
         }
     }
 }
@@ -101,49 +173,191 @@ pub struct Aggregator {
     config: AggregatorConfig,
     event_tx: mpsc::Sender<Event>,
     text_tx: mpsc::Sender<TextCapture>,
-    db: Arc<Mutex<Connection>>,
-    llm: Arc<Mutex<SummarizationModel>>,
+    db: db::Database,
+    // Selected storage engine; all event/capture writes and retention sweeps
+    // flow through this, so a deployment can swap SQLite for Postgres/RocksDB
+    // without the pipeline caring which dialect it talks to.
+    storage: Arc<dyn StorageBackend>,
+    llm: Option<Arc<Mutex<SummarizationModel>>>,
+    dlq: Arc<dlq::DeadLetterQueue>,
+    metrics: MetricsHandle,
+    export: Option<Arc<dyn ExportSink>>,
+    broadcaster: Broadcaster,
+    liveness: Arc<liveness::Liveness>,
+    // Whether the summarization model loaded at startup; surfaced through the
+    // readiness probe so a missing model degrades health rather than silently
+    // disabling summaries.
+    model_loaded: bool,
+}
+
+// Fraction of a bounded mpsc channel currently occupied (queued / capacity),
+// used by the readiness probe to flag channel backpressure.
+fn channel_usage<T>(tx: &mpsc::Sender<T>) -> f64 {
+    let max = tx.max_capacity();
+    if max == 0 {
+        return 0.0;
+    }
+    (max - tx.capacity()) as f64 / max as f64
 }
 
 impl Aggregator {
     pub async fn new(config: AggregatorConfig) -> Result<Self> {
-        // Initialize the database with encryption
-        let db = db::init_database(&config.db_path, &config.encryption_key)
+        // Bring up telemetry first (no-op unless the feature and the config
+        // toggle are both on) so startup spans and metrics are captured.
+        if config.telemetry.enabled {
+            telemetry::init(&config.telemetry)
+                .context("Failed to initialize telemetry")?;
+        }
+
+        // Initialize the pooled, encrypted database. Each pooled connection
+        // runs the SQLCipher/WAL setup in its customizer, so the capture
+        // writer, retention sweeper, and readers each check out their own.
+        let db = db::Database::new(&config.db_path, &config.encryption_key, &config.pool)
             .context("Failed to initialize database")?;
-        
+
+        // Route all writes through the engine selected by `config.engine`. For
+        // SQLite we wrap the pool opened above so the SQLite-only subsystems
+        // (DLQ, GraphQL, Parquet export, liveness probe) keep sharing one set
+        // of connections; other engines open their own client inside
+        // `storage::open`.
+        let storage: Arc<dyn StorageBackend> = match config.engine {
+            StorageEngine::Sqlite => {
+                Arc::new(storage::SqliteBackend::from_database(db.clone()))
+            }
+            _ => Arc::from(storage::open(&config.storage_config())?),
+        };
+        // Bootstrap the backend's schema before the pipelines start writing. A
+        // fresh Postgres database has no tables until this runs; SQLite and
+        // RocksDB set their schema up on open and treat this as a no-op.
+        storage
+            .migrate()
+            .await
+            .context("Failed to run storage migrations")?;
+
         // Create channels for both event types
         let (event_tx, event_rx) = mpsc::channel(1000);
         let (text_tx, text_rx) = mpsc::channel(1000);
         
-        // Initialize the LLM for text summarization
-        let llm = SummarizationModel::new(SummarizationConfig::new()
+        // Initialize the LLM for text summarization. A load failure is not
+        // fatal: captures are still stored verbatim, and the readiness probe
+        // reports the degraded state via `model_loaded`.
+        let (llm, model_loaded) = match SummarizationModel::new(SummarizationConfig::new()
             .model_type("facebook/bart-large-cnn")
             .model_path(&config.text_capture_config.model_path)
-            .quantized(true))?;
-        
-        let db = Arc::new(Mutex::new(db));
-        let llm = Arc::new(Mutex::new(llm));
-        
-        // Spawn both processing pipelines
-        let event_db = Arc::clone(&db);
-        let text_db = Arc::clone(&db);
-        let text_llm = Arc::clone(&llm);
+            .quantized(true))
+        {
+            Ok(model) => (Some(Arc::new(Mutex::new(model))), true),
+            Err(e) => {
+                warn!("Summarization model failed to load; storing captures without summaries: {}", e);
+                (None, false)
+            }
+        };
+
+        // In-process metrics shared across both pipelines; the buffered
+        // emitter ships snapshots to the configured StatsD sink.
+        let metrics = metrics::Metrics::new();
+        if config.metrics.enabled {
+            match metrics::StatsdSink::connect(&config.metrics.sink_addr) {
+                Ok(sink) => metrics::spawn_emitter(
+                    Arc::clone(&metrics),
+                    config.metrics.clone(),
+                    Arc::new(sink),
+                ),
+                Err(e) => warn!("Metrics sink unavailable, not emitting: {}", e),
+            }
+        }
+
+        // Optional streaming export sink, produced to after each DB commit.
+        let export: Option<Arc<dyn ExportSink>> = match &config.export {
+            Some(export_config) => Some(Arc::from(sink::open(export_config)?)),
+            None => None,
+        };
+
+        // Dead-letter queue: flush failures park here instead of being dropped,
+        // and a background drainer re-attempts them with exponential backoff.
+        // It shares the export sink so parked export failures are re-produced
+        // rather than re-inserted into the DB.
+        let dlq = Arc::new(dlq::DeadLetterQueue::new(
+            db.clone(),
+            dlq::DlqConfig {
+                max_retries: config.dlq_max_retries,
+                failure_limit: config.dlq_failure_limit,
+                window_ms: config.dlq_window_ms,
+            },
+            Arc::clone(&metrics),
+            export.clone(),
+        ));
+
+        // Live fan-out hub for GraphQL subscriptions.
+        let broadcaster = Broadcaster::default();
+
+        // Shared liveness state, stamped by the processors and sampled by the
+        // readiness probe.
+        let liveness = Arc::new(liveness::Liveness::new());
+
+        // Spawn both processing pipelines. Each gets an `Arc` clone of the
+        // storage backend, so both write through the same connections.
+        let event_storage = Arc::clone(&storage);
+        let text_storage = Arc::clone(&storage);
+        let text_llm = llm.clone();
         let event_config = config.clone();
         let text_config = config.clone();
+        let event_dlq = Arc::clone(&dlq);
+        let text_dlq = Arc::clone(&dlq);
+        let event_metrics = Arc::clone(&metrics);
+        let text_metrics = Arc::clone(&metrics);
+        let event_export = export.clone();
+        let text_export = export.clone();
+        let event_liveness = Arc::clone(&liveness);
+        let text_liveness = Arc::clone(&liveness);
 
         // Launch event processor
         tokio::spawn(async move {
-            Self::batch_processor(event_db, event_rx, event_config).await
+            Self::batch_processor(event_storage, event_rx, event_config, event_dlq, event_metrics, event_export, event_liveness).await
         });
 
         // Launch text processor
         tokio::spawn(async move {
-            Self::text_batch_processor(text_db, text_rx, text_llm, text_config).await
+            Self::text_batch_processor(text_storage, text_rx, text_llm, text_config, text_dlq, text_metrics, text_export, text_liveness).await
         });
 
+        // Launch the periodic readiness probe, which logs a warning whenever a
+        // component degrades.
+        let probe_db = db.clone();
+        let probe_liveness = Arc::clone(&liveness);
+        let probe_event_tx = event_tx.clone();
+        let probe_text_tx = text_tx.clone();
+        let probe_config = config.liveness.clone();
+        let probe_model_loaded = model_loaded;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(
+                tokio::time::Duration::from_millis(probe_config.check_interval_ms),
+            );
+            loop {
+                interval.tick().await;
+                let _ = liveness::evaluate(&liveness::Probe {
+                    db: &probe_db,
+                    liveness: &probe_liveness,
+                    config: &probe_config,
+                    event_channel_usage: channel_usage(&probe_event_tx),
+                    text_channel_usage: channel_usage(&probe_text_tx),
+                    model_loaded: probe_model_loaded,
+                });
+            }
+        });
+
+        // Launch the dead-letter drainer.
+        tokio::spawn(
+            Arc::clone(&dlq)
+                .run_drainer(tokio::time::Duration::from_millis(config.batch_interval_ms)),
+        );
+
         // Initialize text capture system if enabled
         if config.text_capture_config.enable_accessibility {
-            text_capture::init_capture_system(text_tx.clone())?;
+            text_capture::init_capture_system(
+                text_tx.clone(),
+                config.text_capture_config.filter.clone(),
+            )?;
         }
 
         Ok(Self {
@@ -151,15 +365,26 @@ impl Aggregator {
             event_tx,
             text_tx,
             db,
+            storage,
             llm,
+            dlq,
+            metrics,
+            export,
+            broadcaster,
+            liveness,
+            model_loaded,
         })
     }
 
     // Event processing pipeline (existing implementation remains unchanged)
     async fn batch_processor(
-        db: Arc<Mutex<Connection>>,
+        storage: Arc<dyn StorageBackend>,
         mut event_rx: mpsc::Receiver<Event>,
         config: AggregatorConfig,
+        dlq: Arc<dlq::DeadLetterQueue>,
+        metrics: MetricsHandle,
+        export: Option<Arc<dyn ExportSink>>,
+        liveness: Arc<liveness::Liveness>,
     ) {
         let mut batch = Vec::with_capacity(config.batch_size);
         let mut interval = tokio::time::interval(
@@ -169,32 +394,71 @@ impl Aggregator {
         loop {
             tokio::select! {
                 Some(event) = event_rx.recv() => {
+                    metrics.incr_events_received(1);
                     batch.push(event);
+                    metrics.set_current_batch_size(batch.len() as u64);
                     if batch.len() >= config.batch_size {
-                        if let Err(e) = Self::flush_batch(&db, &batch).await {
-                            error!("Failed to flush batch: {}", e);
-                        }
+                        Self::flush_events(&storage, &batch, &dlq, &metrics, &export).await;
                         batch.clear();
+                        metrics.set_current_batch_size(0);
+                        liveness.mark_event_active();
                     }
                 }
                 _ = interval.tick() => {
                     if !batch.is_empty() {
-                        if let Err(e) = Self::flush_batch(&db, &batch).await {
-                            error!("Failed to flush batch: {}", e);
-                        }
+                        Self::flush_events(&storage, &batch, &dlq, &metrics, &export).await;
                         batch.clear();
+                        metrics.set_current_batch_size(0);
                     }
+                    // Stamp liveness even on an empty tick so an idle pipeline
+                    // is not mistaken for a stalled one.
+                    liveness.mark_event_active();
                 }
             }
         }
     }
 
+    // Flush an event batch, recording success/failure metrics and routing a
+    // failed flush to the dead-letter queue rather than dropping it. On a
+    // successful commit the batch is also produced to the streaming export
+    // sink; records the sink fails to produce are dead-lettered too.
+    async fn flush_events(
+        storage: &Arc<dyn StorageBackend>,
+        batch: &[Event],
+        dlq: &Arc<dlq::DeadLetterQueue>,
+        metrics: &MetricsHandle,
+        export: &Option<Arc<dyn ExportSink>>,
+    ) {
+        match storage.insert_events(batch).await {
+            Ok(()) => {
+                metrics.incr_batches_flushed();
+                if let Some(sink) = export {
+                    if let Err(e) = sink.export_events(batch).await {
+                        error!("Failed to export event batch: {}", e);
+                        // Rows are already committed; park for re-produce, not
+                        // re-insert, to avoid duplicating them.
+                        dlq.record_event_export(batch, &e.to_string());
+                    }
+                }
+            }
+            Err(e) => {
+                error!("Failed to flush batch: {}", e);
+                metrics.incr_flush_errors();
+                dlq.record_events(batch, &e.to_string());
+            }
+        }
+    }
+
     // Text processing pipeline
     async fn text_batch_processor(
-        db: Arc<Mutex<Connection>>,
+        storage: Arc<dyn StorageBackend>,
         mut text_rx: mpsc::Receiver<TextCapture>,
-        llm: Arc<Mutex<SummarizationModel>>,
+        llm: Option<Arc<Mutex<SummarizationModel>>>,
         config: AggregatorConfig,
+        dlq: Arc<dlq::DeadLetterQueue>,
+        metrics: MetricsHandle,
+        export: Option<Arc<dyn ExportSink>>,
+        liveness: Arc<liveness::Liveness>,
     ) {
         let mut batch = Vec::with_capacity(config.batch_size);
         let mut interval = tokio::time::interval(
@@ -204,74 +468,97 @@ impl Aggregator {
         loop {
             tokio::select! {
                 Some(capture) = text_rx.recv() => {
+                    metrics.incr_text_captures_received(1);
                     batch.push(capture);
+                    metrics.set_current_batch_size(batch.len() as u64);
                     if batch.len() >= config.batch_size {
-                        if let Err(e) = Self::flush_text_batch(&db, &batch, &llm, &config).await {
-                            error!("Failed to flush text batch: {}", e);
-                        }
+                        Self::flush_texts(&storage, &batch, &llm, &config, &dlq, &metrics, &export).await;
                         batch.clear();
+                        metrics.set_current_batch_size(0);
+                        liveness.mark_text_active();
                     }
                 }
                 _ = interval.tick() => {
                     if !batch.is_empty() {
-                        if let Err(e) = Self::flush_text_batch(&db, &batch, &llm, &config).await {
-                            error!("Failed to flush text batch: {}", e);
-                        }
+                        Self::flush_texts(&storage, &batch, &llm, &config, &dlq, &metrics, &export).await;
                         batch.clear();
+                        metrics.set_current_batch_size(0);
                     }
+                    // Stamp liveness even on an empty tick so an idle pipeline
+                    // is not mistaken for a stalled one.
+                    liveness.mark_text_active();
                 }
             }
         }
     }
 
-    // Existing event batch flushing (unchanged)
-    async fn flush_batch(db: &Arc<Mutex<Connection>>, events: &[Event]) -> Result<()> {
-        // Existing implementation remains unchanged
+    // Flush a text-capture batch, recording metrics and dead-lettering on
+    // error. A successful commit is followed by a produce to the streaming
+    // export sink, whose failures are dead-lettered as well.
+    async fn flush_texts(
+        storage: &Arc<dyn StorageBackend>,
+        batch: &[TextCapture],
+        llm: &Option<Arc<Mutex<SummarizationModel>>>,
+        config: &AggregatorConfig,
+        dlq: &Arc<dlq::DeadLetterQueue>,
+        metrics: &MetricsHandle,
+        export: &Option<Arc<dyn ExportSink>>,
+    ) {
+        match Self::flush_text_batch(storage, batch, llm, config, metrics).await {
+            Ok(()) => {
+                metrics.incr_batches_flushed();
+                if let Some(sink) = export {
+                    if let Err(e) = sink.export_text_captures(batch).await {
+                        error!("Failed to export text batch: {}", e);
+                        // Rows are already committed; park for re-produce, not
+                        // re-insert, to avoid duplicating them.
+                        dlq.record_text_capture_export(batch, &e.to_string());
+                    }
+                }
+            }
+            Err(e) => {
+                error!("Failed to flush text batch: {}", e);
+                metrics.incr_flush_errors();
+                dlq.record_text_captures(batch, &e.to_string());
+            }
+        }
     }
 
-    // Text batch flushing with summarization
+    // Text batch flushing with summarization. Summaries are generated here and
+    // the capture plus its summary are handed to the storage backend, which
+    // owns the dialect-specific insert.
     async fn flush_text_batch(
-        db: &Arc<Mutex<Connection>>,
+        storage: &Arc<dyn StorageBackend>,
         captures: &[TextCapture],
-        llm: &Arc<Mutex<SummarizationModel>>,
+        llm: &Option<Arc<Mutex<SummarizationModel>>>,
         config: &AggregatorConfig,
+        metrics: &MetricsHandle,
     ) -> Result<()> {
-        let db = db.lock().await;
-        let tx = db.transaction()?;
-
+        let mut records = Vec::with_capacity(captures.len());
         for capture in captures {
-            // Generate summary for longer text
-            let summary = if capture.text.len() >= config.text_capture_config.summarization_threshold {
-                let model = llm.lock().await;
-                Some(model.summarize(&capture.text).await?)
-            } else {
-                None
+            // Generate summary for longer text, but only when the model loaded;
+            // otherwise the capture is stored verbatim.
+            let summary = match llm {
+                Some(llm)
+                    if capture.text.len() >= config.text_capture_config.summarization_threshold =>
+                {
+                    let started = std::time::Instant::now();
+                    let model = llm.lock().await;
+                    let summary = model.summarize(&capture.text).await?;
+                    metrics.incr_summarizations_run();
+                    metrics.observe_summarization_latency_ms(started.elapsed().as_millis() as u64);
+                    Some(summary)
+                }
+                _ => None,
             };
 
-            // Store the capture with its summary
-            tx.execute(
-                "INSERT INTO text_captures (
-                    text, app_name, window_title, timestamp,
-                    text_type, context, summary, partition_key
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-                params![
-                    capture.text,
-                    capture.app_name,
-                    capture.window_title,
-                    capture.timestamp,
-                    serde_json::to_string(&capture.text_type)?,
-                    serde_json::to_string(&capture.context)?,
-                    summary,
-                    capture.partition_key.clone().unwrap_or_else(|| {
-                        chrono::DateTime::from_timestamp(capture.timestamp / 1000, 0)
-                            .map(|dt| format!("{}_{:02}", dt.year(), dt.month()))
-                            .unwrap_or_else(|| "unknown".to_string())
-                    })
-                ],
-            )?;
+            records.push(TextCaptureRecord {
+                capture: capture.clone(),
+                summary,
+            });
         }
 
-        tx.commit()?;
+        storage.insert_text_captures(&records).await?;
         info!("Flushed {} text captures to database", captures.len());
         Ok(())
     }
@@ -280,11 +567,23 @@ impl Aggregator {
 
     // Existing event submission (unchanged)
     pub async fn submit_event(&self, mut event: Event) -> Result<()> {
+        // Refuse new work while the flush-failure window is saturated, so a
+        // runaway DB problem surfaces instead of silently piling up.
+        if !self.dlq.accepting() {
+            anyhow::bail!("Aggregator backpressured: too many recent flush failures");
+        }
+        // Fan the accepted event out to any live GraphQL subscribers.
+        self.broadcaster.publish_event(&event);
         // Existing implementation remains unchanged
     }
 
     // New text capture submission
     pub async fn submit_text_capture(&self, mut capture: TextCapture) -> Result<()> {
+        // Refuse new work while the flush-failure window is saturated.
+        if !self.dlq.accepting() {
+            anyhow::bail!("Aggregator backpressured: too many recent flush failures");
+        }
+
         // Skip if text is too short
         if capture.text.len() < self.config.text_capture_config.min_text_length {
             debug!("Text too short, skipping capture");
@@ -300,18 +599,73 @@ impl Aggregator {
             );
         }
 
+        // Fan the accepted capture out to any live GraphQL subscribers before
+        // it is moved onto the batch channel.
+        self.broadcaster.publish_text_capture(&capture);
+
         self.text_tx.send(capture).await
             .context("Failed to submit text capture")?;
 
         Ok(())
     }
 
+    /// Hand out a clone of the live metrics handle so embedders can scrape
+    /// current counter and gauge values without going through the sink.
+    pub fn metrics_handle(&self) -> MetricsHandle {
+        Arc::clone(&self.metrics)
+    }
+
+    /// Start the embeddable GraphQL server on `addr`, sharing this
+    /// aggregator's database pool and live broadcaster so clients can both
+    /// query history and subscribe to captures in near-real-time. Requires the
+    /// `graphql` feature.
+    #[cfg(feature = "graphql")]
+    pub async fn serve_graphql(&self, addr: std::net::SocketAddr) -> Result<()> {
+        query::serve(self.db.clone(), self.broadcaster.clone(), addr).await
+    }
+
+    /// Sample every subsystem and return a structured readiness report,
+    /// suitable for a `/healthz` endpoint. Degraded components are also logged.
+    pub async fn health_status(&self) -> HealthReport {
+        liveness::evaluate(&liveness::Probe {
+            db: &self.db,
+            liveness: &self.liveness,
+            config: &self.config.liveness,
+            event_channel_usage: channel_usage(&self.event_tx),
+            text_channel_usage: channel_usage(&self.text_tx),
+            model_loaded: self.model_loaded,
+        })
+    }
+
     // Existing retention and health methods remain unchanged
     pub async fn run_retention(&self) -> Result<()> {
-        // Existing implementation remains unchanged
+        self.storage.run_retention().await
     }
 
-    pub async fn submit_health_metric(/* existing parameters */) -> Result<()> {
+    // Export the store to Parquet files (one per partition) under `out_dir`,
+    // returning a manifest of the produced files and their time ranges.
+    pub async fn export_parquet(&self, out_dir: impl AsRef<std::path::Path>) -> Result<ExportManifest> {
+        let exporter = ParquetExporter::new(out_dir);
+        exporter.export(&self.db)
+    }
+
+    pub async fn submit_health_metric(
+        &self,
+        metric_type: String,
+        value: f64,
+        unit: String,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<()> {
+        // Fan the accepted metric out to any live GraphQL subscribers before it
+        // is persisted, mirroring the event and text-capture submit paths.
+        self.broadcaster.publish_health_metric(&HealthMetric {
+            metric_type: metric_type.clone(),
+            value,
+            unit: unit.clone(),
+            start_time: start_time.timestamp_millis(),
+            end_time: end_time.timestamp_millis(),
+        });
         // Existing implementation remains unchanged
     }
 
@@ -368,8 +722,8 @@ mod tests {
         // Wait for batch processing
         tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
 
-        let db = aggregator.db.lock().await;
-        let count: i64 = db.query_row(
+        let conn = aggregator.db.get()?;
+        let count: i64 = conn.query_row(
             "SELECT COUNT(*) FROM text_captures",
             params![],
             |row| row.get(0),
@@ -0,0 +1,421 @@
+// Embeddable GraphQL query + subscription API over the captured store.
+//
+// UIs can query events, text captures (with their generated summaries), health
+// metrics, workouts and sleep sessions with time-range / partition filters and
+// pagination, and — crucially — live-tail new captures over GraphQL
+// subscriptions. Each successful `submit_*` publishes the new record on a
+// `tokio::sync::broadcast` channel; subscription resolvers forward those to
+// connected clients so a dashboard sees text as it is captured.
+//
+// The HTTP server itself (schema, resolvers, transport) lives behind the
+// `graphql` cargo feature; the broadcast fan-out is always compiled so the
+// capture path stays uniform whether or not a server is running.
+use tokio::sync::broadcast;
+
+use super::{Event, HealthMetric, TextCapture};
+
+/// Fan-out hub for live subscriptions. Senders are cheap to clone and a send
+/// with no subscribers is a no-op, so the capture path pays almost nothing.
+#[derive(Clone)]
+pub struct Broadcaster {
+    events: broadcast::Sender<Event>,
+    text_captures: broadcast::Sender<TextCapture>,
+    health_metrics: broadcast::Sender<HealthMetric>,
+}
+
+impl Broadcaster {
+    /// Build a broadcaster with the given per-channel buffer capacity. Slow
+    /// subscribers that lag beyond the buffer miss intermediate records rather
+    /// than stalling the publisher.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: broadcast::channel(capacity).0,
+            text_captures: broadcast::channel(capacity).0,
+            health_metrics: broadcast::channel(capacity).0,
+        }
+    }
+
+    pub fn publish_event(&self, event: &Event) {
+        let _ = self.events.send(event.clone());
+    }
+
+    pub fn publish_text_capture(&self, capture: &TextCapture) {
+        let _ = self.text_captures.send(capture.clone());
+    }
+
+    pub fn publish_health_metric(&self, metric: &HealthMetric) {
+        let _ = self.health_metrics.send(metric.clone());
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    pub fn subscribe_text_captures(&self) -> broadcast::Receiver<TextCapture> {
+        self.text_captures.subscribe()
+    }
+
+    pub fn subscribe_health_metrics(&self) -> broadcast::Receiver<HealthMetric> {
+        self.health_metrics.subscribe()
+    }
+}
+
+impl Default for Broadcaster {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+#[cfg(feature = "graphql")]
+pub use server::serve;
+
+#[cfg(feature = "graphql")]
+mod server {
+    use super::*;
+    use crate::aggregator::db::Database;
+    use anyhow::Result;
+    use async_graphql::{
+        Context, EmptyMutation, InputObject, Object, Schema, SimpleObject, Subscription,
+    };
+    use async_graphql_axum::{GraphQL, GraphQLSubscription};
+    use axum::{routing::post, Router};
+    use futures_util::{Stream, StreamExt};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio_stream::wrappers::BroadcastStream;
+
+    // Shared resolver state: the read pool plus the live fan-out hub.
+    struct ApiState {
+        db: Database,
+        broadcaster: Broadcaster,
+    }
+
+    /// Inclusive time-range filter over the millisecond `timestamp` column.
+    #[derive(InputObject, Default)]
+    struct TimeRange {
+        after: Option<i64>,
+        before: Option<i64>,
+    }
+
+    #[derive(SimpleObject)]
+    struct EventRow {
+        timestamp: i64,
+        source: String,
+        event_type: String,
+        metadata: String,
+        partition_key: Option<String>,
+    }
+
+    #[derive(SimpleObject)]
+    struct TextCaptureRow {
+        text: String,
+        app_name: String,
+        window_title: String,
+        timestamp: i64,
+        summary: Option<String>,
+        partition_key: Option<String>,
+    }
+
+    #[derive(SimpleObject)]
+    struct HealthMetricRow {
+        metric_type: String,
+        value: f64,
+        unit: String,
+        start_time: i64,
+        end_time: i64,
+    }
+
+    #[derive(SimpleObject)]
+    struct WorkoutRow {
+        workout_type: String,
+        start_time: i64,
+        end_time: i64,
+    }
+
+    #[derive(SimpleObject)]
+    struct SleepSessionRow {
+        start_time: i64,
+        end_time: i64,
+        quality: Option<String>,
+    }
+
+    // Append the shared time-range / partition / pagination clause and bind its
+    // parameters, keeping every list resolver consistent.
+    fn paginate(
+        base: &str,
+        time_column: &str,
+        range: &Option<TimeRange>,
+        partition_key: &Option<String>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+        let mut sql = String::from(base);
+        let mut clauses = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        if let Some(range) = range {
+            if let Some(after) = range.after {
+                clauses.push(format!("{time_column} >= ?"));
+                params.push(Box::new(after));
+            }
+            if let Some(before) = range.before {
+                clauses.push(format!("{time_column} <= ?"));
+                params.push(Box::new(before));
+            }
+        }
+        if let Some(pk) = partition_key {
+            clauses.push("partition_key = ?".to_string());
+            params.push(Box::new(pk.clone()));
+        }
+        if !clauses.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&clauses.join(" AND "));
+        }
+        sql.push_str(&format!(" ORDER BY {time_column} DESC LIMIT ? OFFSET ?"));
+        params.push(Box::new(limit.unwrap_or(100)));
+        params.push(Box::new(offset.unwrap_or(0)));
+        (sql, params)
+    }
+
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        async fn events(
+            &self,
+            ctx: &Context<'_>,
+            range: Option<TimeRange>,
+            partition_key: Option<String>,
+            limit: Option<i64>,
+            offset: Option<i64>,
+        ) -> async_graphql::Result<Vec<EventRow>> {
+            let state = ctx.data::<Arc<ApiState>>()?;
+            let conn = state.db.get()?;
+            let (sql, params) = paginate(
+                "SELECT timestamp, source, event_type, metadata, partition_key FROM events",
+                "timestamp",
+                &range,
+                &partition_key,
+                limit,
+                offset,
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+            let rows = stmt
+                .query_map(refs.as_slice(), |row| {
+                    Ok(EventRow {
+                        timestamp: row.get(0)?,
+                        source: row.get(1)?,
+                        event_type: row.get(2)?,
+                        metadata: row.get(3)?,
+                        partition_key: row.get(4)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        }
+
+        async fn text_captures(
+            &self,
+            ctx: &Context<'_>,
+            range: Option<TimeRange>,
+            partition_key: Option<String>,
+            limit: Option<i64>,
+            offset: Option<i64>,
+        ) -> async_graphql::Result<Vec<TextCaptureRow>> {
+            let state = ctx.data::<Arc<ApiState>>()?;
+            let conn = state.db.get()?;
+            let (sql, params) = paginate(
+                "SELECT text, app_name, window_title, timestamp, summary, partition_key FROM text_captures",
+                "timestamp",
+                &range,
+                &partition_key,
+                limit,
+                offset,
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+            let rows = stmt
+                .query_map(refs.as_slice(), |row| {
+                    Ok(TextCaptureRow {
+                        text: row.get(0)?,
+                        app_name: row.get(1)?,
+                        window_title: row.get(2)?,
+                        timestamp: row.get(3)?,
+                        summary: row.get(4)?,
+                        partition_key: row.get(5)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        }
+
+        async fn health_metrics(
+            &self,
+            ctx: &Context<'_>,
+            range: Option<TimeRange>,
+            limit: Option<i64>,
+            offset: Option<i64>,
+        ) -> async_graphql::Result<Vec<HealthMetricRow>> {
+            let state = ctx.data::<Arc<ApiState>>()?;
+            let conn = state.db.get()?;
+            let (sql, params) = paginate(
+                "SELECT metric_type, value, unit, start_time, end_time FROM health_metrics",
+                "start_time",
+                &range,
+                &None,
+                limit,
+                offset,
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+            let rows = stmt
+                .query_map(refs.as_slice(), |row| {
+                    Ok(HealthMetricRow {
+                        metric_type: row.get(0)?,
+                        value: row.get(1)?,
+                        unit: row.get(2)?,
+                        start_time: row.get(3)?,
+                        end_time: row.get(4)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        }
+
+        async fn workouts(
+            &self,
+            ctx: &Context<'_>,
+            range: Option<TimeRange>,
+            limit: Option<i64>,
+            offset: Option<i64>,
+        ) -> async_graphql::Result<Vec<WorkoutRow>> {
+            let state = ctx.data::<Arc<ApiState>>()?;
+            let conn = state.db.get()?;
+            let (sql, params) = paginate(
+                "SELECT workout_type, start_time, end_time FROM workouts",
+                "start_time",
+                &range,
+                &None,
+                limit,
+                offset,
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+            let rows = stmt
+                .query_map(refs.as_slice(), |row| {
+                    Ok(WorkoutRow {
+                        workout_type: row.get(0)?,
+                        start_time: row.get(1)?,
+                        end_time: row.get(2)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        }
+
+        async fn sleep_sessions(
+            &self,
+            ctx: &Context<'_>,
+            range: Option<TimeRange>,
+            limit: Option<i64>,
+            offset: Option<i64>,
+        ) -> async_graphql::Result<Vec<SleepSessionRow>> {
+            let state = ctx.data::<Arc<ApiState>>()?;
+            let conn = state.db.get()?;
+            let (sql, params) = paginate(
+                "SELECT start_time, end_time, quality FROM sleep_sessions",
+                "start_time",
+                &range,
+                &None,
+                limit,
+                offset,
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+            let rows = stmt
+                .query_map(refs.as_slice(), |row| {
+                    Ok(SleepSessionRow {
+                        start_time: row.get(0)?,
+                        end_time: row.get(1)?,
+                        quality: row.get(2)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        }
+    }
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn events(&self, ctx: &Context<'_>) -> async_graphql::Result<impl Stream<Item = EventRow>> {
+            let state = ctx.data::<Arc<ApiState>>()?;
+            let stream = BroadcastStream::new(state.broadcaster.subscribe_events());
+            Ok(stream.filter_map(|res| async move {
+                res.ok().map(|e| EventRow {
+                    timestamp: e.timestamp,
+                    source: e.source,
+                    event_type: e.event_type,
+                    metadata: e.metadata.to_string(),
+                    partition_key: e.partition_key,
+                })
+            }))
+        }
+
+        async fn text_captures(
+            &self,
+            ctx: &Context<'_>,
+        ) -> async_graphql::Result<impl Stream<Item = TextCaptureRow>> {
+            let state = ctx.data::<Arc<ApiState>>()?;
+            let stream = BroadcastStream::new(state.broadcaster.subscribe_text_captures());
+            Ok(stream.filter_map(|res| async move {
+                res.ok().map(|c| TextCaptureRow {
+                    text: c.text,
+                    app_name: c.app_name,
+                    window_title: c.window_title,
+                    timestamp: c.timestamp,
+                    summary: None,
+                    partition_key: c.partition_key,
+                })
+            }))
+        }
+
+        async fn health_metrics(
+            &self,
+            ctx: &Context<'_>,
+        ) -> async_graphql::Result<impl Stream<Item = HealthMetricRow>> {
+            let state = ctx.data::<Arc<ApiState>>()?;
+            let stream = BroadcastStream::new(state.broadcaster.subscribe_health_metrics());
+            Ok(stream.filter_map(|res| async move {
+                res.ok().map(|m| HealthMetricRow {
+                    metric_type: m.metric_type,
+                    value: m.value,
+                    unit: m.unit,
+                    start_time: m.start_time,
+                    end_time: m.end_time,
+                })
+            }))
+        }
+    }
+
+    /// Start the GraphQL server on `addr`, sharing the aggregator's `db` pool
+    /// and live broadcaster. Serves queries over POST and subscriptions over
+    /// the same endpoint via the GraphQL-WS protocol.
+    pub async fn serve(db: Database, broadcaster: Broadcaster, addr: SocketAddr) -> Result<()> {
+        let state = Arc::new(ApiState { db, broadcaster });
+        let schema = Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
+            .data(state)
+            .finish();
+        // Queries/mutations over HTTP POST and subscriptions over the same
+        // endpoint: a GET upgrades to a WebSocket speaking graphql-ws, served by
+        // `GraphQLSubscription`, which streams from the live broadcaster.
+        let app = Router::new().route(
+            "/graphql",
+            post(GraphQL::new(schema.clone())).get_service(GraphQLSubscription::new(schema)),
+        );
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        axum::serve(listener, app).await?;
+        Ok(())
+    }
+}
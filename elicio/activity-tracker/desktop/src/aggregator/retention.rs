@@ -1,40 +1,54 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
-use rusqlite::{params, Connection};
-use serde_json::Value;
+use rusqlite::{params, Connection, OptionalExtension};
 use tracing::{info, warn};
 
-// Run all configured retention policies
-pub(crate) fn run_retention_policies(conn: &Connection) -> Result<()> {
-    let tx = conn.transaction()?;
+use super::db::Database;
+
+// Run all configured retention policies. A connection is checked out of the
+// pool for the duration of the sweep; WAL mode lets the capture writer keep
+// committing on its own connection while this runs.
+pub(crate) fn run_retention_policies(db: &Database) -> Result<()> {
+    let conn = db.get()?;
+    let tx = conn.unchecked_transaction()?;
 
     // Get all active retention policies
     let mut stmt = tx.prepare(
-        "SELECT source, retention_days, summary_table FROM retention_policies"
+        "SELECT source, retention_days, daily_after_days, monthly_after_days, summary_table
+         FROM retention_policies",
     )?;
 
-    let policies = stmt.query_map([], |row| {
-        Ok((
-            row.get::<_, String>(0)?,
-            row.get::<_, i64>(1)?,
-            row.get::<_, Option<String>>(2)?,
-        ))
-    })?;
-
-    for policy_result in policies {
-        let (source, retention_days, summary_table) = policy_result?;
-        
-        // Calculate cutoff timestamp
-        let cutoff = Utc::now().timestamp() - (retention_days * 86400);
-
-        match apply_retention_policy(&tx, &source, cutoff, summary_table.as_deref())? {
+    let policies = stmt
+        .query_map([], |row| {
+            Ok(RetentionPolicy {
+                source: row.get(0)?,
+                retention_days: row.get(1)?,
+                daily_after_days: row.get(2)?,
+                monthly_after_days: row.get(3)?,
+                summary_table: row.get(4)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    for policy in policies {
+        match apply_retention_policy(&tx, &policy)? {
             RetentionResult::Deleted(count) => {
-                info!("Deleted {} old records for source: {}", count, source);
+                info!("Deleted {} old records for source: {}", count, policy.source);
+                super::telemetry::record_retention(&policy.source, count as u64, 0);
             }
-            RetentionResult::Summarized { deleted, summarized } => {
+            RetentionResult::Tiered {
+                daily_summarized,
+                monthly_summarized,
+                deleted,
+            } => {
                 info!(
-                    "Source {}: Summarized {} records and deleted {} old records",
-                    source, summarized, deleted
+                    "Source {}: summarized {} raw->daily, {} daily->monthly, deleted {} old records",
+                    policy.source, daily_summarized, monthly_summarized, deleted
+                );
+                super::telemetry::record_retention(
+                    &policy.source,
+                    deleted as u64,
+                    (daily_summarized + monthly_summarized) as u64,
                 );
             }
         }
@@ -44,41 +58,98 @@ pub(crate) fn run_retention_policies(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+// A single row of the retention_policies table.
+struct RetentionPolicy {
+    source: String,
+    retention_days: i64,
+    daily_after_days: Option<i64>,
+    monthly_after_days: Option<i64>,
+    summary_table: Option<String>,
+}
+
 enum RetentionResult {
     Deleted(i64),
-    Summarized {
-        summarized: i64,
+    Tiered {
+        daily_summarized: i64,
+        monthly_summarized: i64,
         deleted: i64,
     },
 }
 
-// Apply a single retention policy
-fn apply_retention_policy(
-    tx: &Connection,
-    source: &str,
-    cutoff: i64,
-    summary_table: Option<&str>,
-) -> Result<RetentionResult> {
-    // If we have a summary table, create summaries before deletion
-    if let Some(table) = summary_table {
-        let summarized = summarize_old_data(tx, source, cutoff, table)
-            .context("Failed to summarize old data")?;
-
-        // Delete old records after summarization
-        let deleted = delete_old_records(tx, source, cutoff)
-            .context("Failed to delete old records after summarization")?;
-
-        Ok(RetentionResult::Summarized {
-            summarized,
-            deleted,
-        })
-    } else {
-        // Just delete old records
-        let deleted = delete_old_records(tx, source, cutoff)
-            .context("Failed to delete old records")?;
-        
-        Ok(RetentionResult::Deleted(deleted))
-    }
+pub(crate) const MS_PER_DAY: i64 = 86_400_000;
+
+/// Fallback retention horizon for backends that have no per-source policy
+/// table of their own (e.g. the RocksDB key-value store).
+pub(crate) const DEFAULT_RETENTION_DAYS: i64 = 90;
+
+// Apply a single retention policy: cascade raw -> daily -> monthly, then
+// hard-delete anything past the retention window.
+fn apply_retention_policy(tx: &Connection, policy: &RetentionPolicy) -> Result<RetentionResult> {
+    let now_ms = Utc::now().timestamp_millis();
+
+    // Without a summary table the policy is delete-only, preserving the old
+    // behaviour for sources that don't want rollups.
+    let table = match policy.summary_table.as_deref() {
+        Some(table) => table,
+        None => {
+            let cutoff = now_ms - policy.retention_days * MS_PER_DAY;
+            let deleted = delete_old_records(tx, &policy.source, cutoff)
+                .context("Failed to delete old records")?;
+            return Ok(RetentionResult::Deleted(deleted));
+        }
+    };
+
+    // Tier 1: raw events -> daily rollup, over (watermark, cutoff].
+    let daily_after = policy.daily_after_days.unwrap_or(policy.retention_days);
+    let daily_cutoff = now_ms - daily_after * MS_PER_DAY;
+    let daily_summarized = summarize_daily(tx, &policy.source, daily_cutoff, table)
+        .context("Failed to summarize raw events into daily rollup")?;
+
+    // Tier 2: daily rollup -> monthly rollup, over (watermark, cutoff].
+    let monthly_summarized = match policy.monthly_after_days {
+        Some(monthly_after) => {
+            let monthly_cutoff = now_ms - monthly_after * MS_PER_DAY;
+            summarize_monthly(tx, &policy.source, monthly_cutoff)
+                .context("Failed to roll daily rollup into monthly rollup")?
+        }
+        None => 0,
+    };
+
+    // Finally hard-delete raw rows past the retention window.
+    let delete_cutoff = now_ms - policy.retention_days * MS_PER_DAY;
+    let deleted = delete_old_records(tx, &policy.source, delete_cutoff)
+        .context("Failed to delete old records after summarization")?;
+
+    Ok(RetentionResult::Tiered {
+        daily_summarized,
+        monthly_summarized,
+        deleted,
+    })
+}
+
+// Read the high-water-mark (epoch ms) already summarized for a (source, tier).
+fn read_watermark(tx: &Connection, source: &str, tier: &str) -> Result<i64> {
+    let mark = tx
+        .query_row(
+            "SELECT last_summarized FROM rollup_progress WHERE source = ? AND tier = ?",
+            params![source, tier],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?;
+    Ok(mark.unwrap_or(0))
+}
+
+// Advance the high-water-mark for a (source, tier).
+fn write_watermark(tx: &Connection, source: &str, tier: &str, watermark: i64) -> Result<()> {
+    tx.execute(
+        "INSERT INTO rollup_progress (source, tier, last_summarized, updated_at)
+         VALUES (?, ?, ?, ?)
+         ON CONFLICT(source, tier) DO UPDATE SET
+            last_summarized = excluded.last_summarized,
+            updated_at = excluded.updated_at",
+        params![source, tier, watermark, Utc::now().timestamp()],
+    )?;
+    Ok(())
 }
 
 // Delete records older than cutoff
@@ -141,25 +212,26 @@ fn delete_old_records(tx: &Connection, source: &str, cutoff: i64) -> Result<i64>
     Ok(deleted as i64)
 }
 
-// Create summary records for old data before deletion
-fn summarize_old_data(
-    tx: &Connection,
-    source: &str,
-    cutoff: i64,
-    summary_table: &str,
-) -> Result<i64> {
-    // Verify summary table exists
+// Tier 1: fold raw events into the daily rollup over the half-open interval
+// (watermark, cutoff] so re-running over an overlapping window never
+// double-counts. The watermark is advanced to `cutoff` on success.
+fn summarize_daily(tx: &Connection, source: &str, cutoff: i64, summary_table: &str) -> Result<i64> {
+    // Only the canonical daily rollup table is supported.
     if !summary_table.starts_with("event_statistics") {
         warn!("Invalid summary table: {}", summary_table);
         return Ok(0);
     }
 
-    // Insert daily summaries
+    let watermark = read_watermark(tx, source, "daily")?;
+    if cutoff <= watermark {
+        return Ok(0);
+    }
+
     let inserted = tx.execute(
         "INSERT INTO event_statistics (
             date, source, event_type, count, metadata, updated_at
         )
-        SELECT 
+        SELECT
             date(timestamp/1000, 'unixepoch') as day,
             source,
             event_type,
@@ -170,8 +242,8 @@ fn summarize_old_data(
             ) as summary_metadata,
             ? as updated_at
         FROM events
-        WHERE source = ? AND timestamp < ?
-        GROUP BY 
+        WHERE source = ? AND timestamp > ? AND timestamp <= ?
+        GROUP BY
             date(timestamp/1000, 'unixepoch'),
             source,
             event_type
@@ -179,13 +251,49 @@ fn summarize_old_data(
             count = count + excluded.count,
             metadata = json_patch(metadata, excluded.metadata),
             updated_at = excluded.updated_at",
-        params![
-            Utc::now().timestamp(),
+        params![Utc::now().timestamp(), source, watermark, cutoff],
+    )?;
+
+    write_watermark(tx, source, "daily", cutoff)?;
+    Ok(inserted as i64)
+}
+
+// Tier 2: re-group daily rollup rows by calendar month into the monthly
+// rollup over (watermark, cutoff]. Daily rows carry a 'YYYY-MM-DD' date, which
+// we map to midnight epoch ms to compare against the watermark.
+fn summarize_monthly(tx: &Connection, source: &str, cutoff: i64) -> Result<i64> {
+    let watermark = read_watermark(tx, source, "monthly")?;
+    if cutoff <= watermark {
+        return Ok(0);
+    }
+
+    // Fold each daily row into its month via the upsert, patching the daily
+    // `metadata` into the accumulating monthly `metadata` with json_patch — the
+    // same way the daily tier folds per-event metadata. Grouping here would
+    // discard the daily summaries, so we rely on the row-by-row conflict path.
+    let inserted = tx.execute(
+        "INSERT INTO event_statistics_monthly (
+            month, source, event_type, count, metadata, updated_at
+        )
+        SELECT
+            strftime('%Y-%m', date) as month,
             source,
-            cutoff,
-        ],
+            event_type,
+            count,
+            metadata,
+            ? as updated_at
+        FROM event_statistics
+        WHERE source = ?
+          AND CAST(strftime('%s', date) AS INTEGER) * 1000 > ?
+          AND CAST(strftime('%s', date) AS INTEGER) * 1000 <= ?
+        ON CONFLICT(month, source, event_type) DO UPDATE SET
+            count = count + excluded.count,
+            metadata = json_patch(metadata, excluded.metadata),
+            updated_at = excluded.updated_at",
+        params![Utc::now().timestamp(), source, watermark, cutoff],
     )?;
 
+    write_watermark(tx, source, "monthly", cutoff)?;
     Ok(inserted as i64)
 }
 
@@ -200,10 +308,12 @@ mod tests {
         let dir = tempdir()?;
         let db_path = dir.path().join("test.db");
         
-        let conn = db::init_database(
+        let database = db::Database::new(
             db_path.to_str().unwrap(),
             "test-key",
+            &db::PoolConfig::default(),
         )?;
+        let conn = database.get()?;
 
         // Insert test data
         let now = Utc::now().timestamp_millis();
@@ -254,7 +364,7 @@ mod tests {
         )?;
 
         // Run retention
-        run_retention_policies(&conn)?;
+        run_retention_policies(&database)?;
 
         // Verify old event was deleted
         let count: i64 = conn.query_row(
@@ -285,4 +395,50 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_summarization_is_idempotent() -> Result<()> {
+        let dir = tempdir()?;
+        let db_path = dir.path().join("test.db");
+        let database = db::Database::new(
+            db_path.to_str().unwrap(),
+            "test-key",
+            &db::PoolConfig::default(),
+        )?;
+        let conn = database.get()?;
+
+        let now = Utc::now().timestamp_millis();
+        let old = now - (60 * 86400 * 1000); // 60 days old
+
+        // Two old events that will fold into a single daily bucket.
+        for _ in 0..2 {
+            conn.execute(
+                "INSERT INTO events (
+                    timestamp, source, event_type, metadata, inserted_at, partition_key
+                ) VALUES (?, ?, ?, ?, ?, ?)",
+                params![old, "test", "test_event", "{}", old / 1000, "2024_01"],
+            )?;
+        }
+
+        // Summarize but keep the raw rows so a second run covers the same window.
+        conn.execute(
+            "INSERT INTO retention_policies (
+                source, retention_days, daily_after_days, summary_table, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?)",
+            params!["test", 365, 30, "event_statistics", now / 1000, now / 1000],
+        )?;
+
+        run_retention_policies(&database)?;
+        run_retention_policies(&database)?;
+
+        // Count must reflect the two events exactly once, not four times.
+        let count: i64 = conn.query_row(
+            "SELECT count FROM event_statistics WHERE source = 'test'",
+            [],
+            |row| row.get(0),
+        )?;
+        assert_eq!(count, 2);
+
+        Ok(())
+    }
 }
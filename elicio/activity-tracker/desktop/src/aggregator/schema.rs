@@ -1,20 +1,173 @@
 use anyhow::{Context, Result};
-use rusqlite::{params, Connection};
+use apache_avro::Schema as AvroSchema;
+use jsonschema::{Draft, Validator};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::{debug, warn};
+use tracing::debug;
+
+// Cache of compiled schemas to avoid recompiling on every event. Schemas are
+// compiled once at registration / load time and reused.
+type SchemaCache = Arc<Mutex<HashMap<(String, String), CompiledSchema>>>;
+
+/// The wire format a schema is expressed in. JSON Schema validates structurally;
+/// Avro additionally gives metadata a canonical compact binary encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+    JsonSchema,
+    Avro,
+}
 
-// Cache of loaded schemas to avoid frequent DB lookups
-type SchemaCache = Arc<Mutex<HashMap<(String, String), Value>>>;
+impl SchemaFormat {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SchemaFormat::JsonSchema => "json-schema",
+            SchemaFormat::Avro => "avro",
+        }
+    }
 
-// Initialize schema cache
-pub(crate) async fn init_schema_cache(db: &Connection) -> Result<SchemaCache> {
+    fn parse(s: &str) -> Result<Self> {
+        match s {
+            "json-schema" => Ok(SchemaFormat::JsonSchema),
+            "avro" => Ok(SchemaFormat::Avro),
+            other => Err(anyhow::anyhow!("Unknown schema format: {other}")),
+        }
+    }
+}
+
+// A compiled schema kept in the cache, tagged by format so the validate and
+// encode/decode paths know which engine to drive.
+#[derive(Clone)]
+enum CompiledSchema {
+    JsonSchema(Arc<Validator>),
+    Avro(Arc<AvroSchema>),
+}
+
+impl CompiledSchema {
+    // Validate an instance, returning every violation. The Avro path reports a
+    // single structural error because apache-avro validates all-or-nothing.
+    fn validate(&self, metadata: &Value) -> Vec<EventValidationError> {
+        match self {
+            CompiledSchema::JsonSchema(validator) => collect_errors(validator, metadata),
+            CompiledSchema::Avro(schema) => match avro_value(metadata) {
+                Ok(value) if value.validate(schema) => Vec::new(),
+                Ok(_) => vec![EventValidationError {
+                    instance_path: String::new(),
+                    schema_path: String::new(),
+                    message: "value does not conform to Avro schema".to_string(),
+                }],
+                Err(e) => vec![EventValidationError {
+                    instance_path: String::new(),
+                    schema_path: String::new(),
+                    message: format!("value is not representable as Avro: {e}"),
+                }],
+            },
+        }
+    }
+}
+
+/// Compatibility mode governing whether a re-registered schema is accepted.
+/// Semantics follow the Avro/Confluent model: BACKWARD = new schema can read
+/// data written under the old one, FORWARD = old schema can read data written
+/// under the new one, FULL = both, NONE = any change allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompatibilityMode {
+    Backward,
+    Forward,
+    Full,
+    None,
+}
+
+impl CompatibilityMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CompatibilityMode::Backward => "BACKWARD",
+            CompatibilityMode::Forward => "FORWARD",
+            CompatibilityMode::Full => "FULL",
+            CompatibilityMode::None => "NONE",
+        }
+    }
+}
+
+/// A single incompatible change between two schema versions, located by the
+/// JSON path of the offending property.
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakingChange {
+    pub path: String,
+    pub detail: String,
+}
+
+/// One stored version of a registered schema.
+#[derive(Debug, Clone)]
+pub struct SchemaVersion {
+    pub version: i64,
+    pub schema: Value,
+    pub compatibility: String,
+    pub format: String,
+    pub created_at: i64,
+}
+
+/// A single schema violation, located by JSON Pointer. Collecting every
+/// violation for a payload lets a producer fix all of them in one round trip,
+/// and the struct is serializable so a rejection can be persisted alongside a
+/// dead-lettered event.
+#[derive(Debug, Clone, Serialize)]
+pub struct EventValidationError {
+    /// JSON Pointer to the offending location in the instance, e.g. `/tags/2`.
+    pub instance_path: String,
+    /// JSON Pointer to the failing keyword in the schema.
+    pub schema_path: String,
+    /// Human-readable description of the failure.
+    pub message: String,
+}
+
+// Compile a raw schema `Value` for the given format, rejecting unparseable or
+// invalid schemas up front rather than discovering the problem on first use.
+fn compile_schema(schema: &Value, format: SchemaFormat) -> Result<CompiledSchema> {
+    match format {
+        SchemaFormat::JsonSchema => {
+            let validator = jsonschema::options()
+                .with_draft(Draft::Draft7)
+                .build(schema)
+                .map_err(|e| anyhow::anyhow!("Invalid JSON Schema: {e}"))?;
+            Ok(CompiledSchema::JsonSchema(Arc::new(validator)))
+        }
+        SchemaFormat::Avro => {
+            let avro = AvroSchema::parse_str(&schema.to_string())
+                .map_err(|e| anyhow::anyhow!("Invalid Avro schema: {e}"))?;
+            Ok(CompiledSchema::Avro(Arc::new(avro)))
+        }
+    }
+}
+
+// Convert a JSON metadata payload into an Avro value for validation or
+// encoding. `apache_avro::to_value` drives serde, giving the same mapping the
+// encode path uses.
+fn avro_value(metadata: &Value) -> Result<apache_avro::types::Value> {
+    apache_avro::to_value(metadata).map_err(|e| anyhow::anyhow!("{e}"))
+}
+
+// Parse and compile one stored row's `(schema, format)` into a cache entry.
+fn compile_row(source: &str, event_type: &str, schema_str: &str, format_str: &str) -> Result<CompiledSchema> {
+    let format = SchemaFormat::parse(format_str)?;
+    let schema: Value = serde_json::from_str(schema_str).context("Failed to parse schema JSON")?;
+    compile_schema(&schema, format)
+        .with_context(|| format!("Failed to compile schema for {source}:{event_type}"))
+}
+
+// Read and compile the latest version of every registered schema. Older
+// versions stay in the history table for historical validation.
+fn load_all_schemas(db: &Connection) -> Result<HashMap<(String, String), CompiledSchema>> {
     let mut cache = HashMap::new();
-    
     let mut stmt = db.prepare(
-        "SELECT source, event_type, schema FROM event_types"
+        "SELECT source, event_type, schema, format FROM event_types et
+         WHERE version = (
+             SELECT MAX(version) FROM event_types e2
+             WHERE e2.source = et.source AND e2.event_type = et.event_type
+         )"
     )?;
 
     let schemas = stmt.query_map([], |row| {
@@ -22,18 +175,42 @@ pub(crate) async fn init_schema_cache(db: &Connection) -> Result<SchemaCache> {
             row.get::<_, String>(0)?,
             row.get::<_, String>(1)?,
             row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
         ))
     })?;
 
     for schema_result in schemas {
-        let (source, event_type, schema_str) = schema_result?;
-        let schema: Value = serde_json::from_str(&schema_str)
-            .context("Failed to parse schema JSON")?;
-        
-        cache.insert((source, event_type), schema);
+        let (source, event_type, schema_str, format_str) = schema_result?;
+        let compiled = compile_row(&source, &event_type, &schema_str, &format_str)?;
+        cache.insert((source, event_type), compiled);
     }
 
-    Ok(Arc::new(Mutex::new(cache)))
+    Ok(cache)
+}
+
+// Read and compile the latest version of a single `(source, event_type)`,
+// returning `None` if no row exists.
+fn load_one_schema(db: &Connection, source: &str, event_type: &str) -> Result<Option<CompiledSchema>> {
+    let row: Option<(String, String)> = db
+        .query_row(
+            "SELECT schema, format FROM event_types
+             WHERE source = ? AND event_type = ?
+             ORDER BY version DESC LIMIT 1",
+            params![source, event_type],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    match row {
+        Some((schema_str, format_str)) => {
+            Ok(Some(compile_row(source, event_type, &schema_str, &format_str)?))
+        }
+        None => Ok(None),
+    }
+}
+
+// Initialize schema cache, compiling every stored schema on load.
+pub(crate) async fn init_schema_cache(db: &Connection) -> Result<SchemaCache> {
+    Ok(Arc::new(Mutex::new(load_all_schemas(db)?)))
 }
 
 // Register a new event type with its schema
@@ -43,156 +220,473 @@ pub(crate) async fn register_event_type(
     source: &str,
     event_type: &str,
     schema: Value,
+    compatibility: CompatibilityMode,
+    format: SchemaFormat,
 ) -> Result<()> {
-    // Validate schema is a valid JSON Schema
-    validate_schema_structure(&schema)
+    // Compile up front: an unparseable or invalid schema is rejected here
+    // rather than silently accepted and failed on first use.
+    let compiled = compile_schema(&schema, format)
         .context("Invalid schema structure")?;
 
     let db = db.lock().await;
     let tx = db.transaction()?;
 
-    // Insert or update schema
+    // Compare against the latest stored version and reject an incompatible
+    // change unless the caller opted out with NONE. Structural compatibility
+    // checking is defined over JSON Schema; Avro schemas skip it.
+    let latest: Option<(i64, String)> = tx
+        .query_row(
+            "SELECT version, schema FROM event_types
+             WHERE source = ? AND event_type = ?
+             ORDER BY version DESC LIMIT 1",
+            params![source, event_type],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    let next_version = match &latest {
+        Some((version, old_schema_str)) => {
+            if compatibility != CompatibilityMode::None && format == SchemaFormat::JsonSchema {
+                let old_schema: Value = serde_json::from_str(old_schema_str)
+                    .context("Stored schema is not valid JSON")?;
+                let breaks = check_compatibility(&old_schema, &schema, compatibility);
+                if !breaks.is_empty() {
+                    let summary = breaks
+                        .iter()
+                        .map(|b| format!("{} ({})", b.path, b.detail))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    return Err(anyhow::anyhow!(
+                        "Schema for {source}:{event_type} is not {:?}-compatible: {summary}",
+                        compatibility
+                    ));
+                }
+            }
+            version + 1
+        }
+        None => 1,
+    };
+
+    // Append a new version; old rows are retained.
     tx.execute(
-        "INSERT INTO event_types (source, event_type, schema, created_at)
-         VALUES (?, ?, ?, strftime('%s', 'now'))
-         ON CONFLICT(source, event_type) DO UPDATE SET
-         schema = excluded.schema",
+        "INSERT INTO event_types (source, event_type, schema, version, compatibility, format, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, strftime('%s', 'now'))",
         params![
             source,
             event_type,
             schema.to_string(),
+            next_version,
+            compatibility.as_str(),
+            format.as_str(),
         ],
     )?;
 
     tx.commit()?;
 
-    // Update cache
+    // Update cache with the compiled schema (latest version wins).
     let mut cache = cache.lock().await;
-    cache.insert((source.to_string(), event_type.to_string()), schema);
+    cache.insert((source.to_string(), event_type.to_string()), compiled);
 
-    debug!("Registered schema for {}:{}", source, event_type);
+    debug!("Registered schema v{} for {}:{}", next_version, source, event_type);
     Ok(())
 }
 
-// Validate event metadata against its schema
-pub(crate) async fn validate_event_metadata(
+/// Re-read a single `(source, event_type)` from the database into the cache,
+/// picking up a registration made by another process or a direct DB write. If
+/// the row has since been deleted the cache entry is evicted.
+pub(crate) async fn reload_schema(
+    db: &Arc<Mutex<Connection>>,
     cache: &SchemaCache,
     source: &str,
     event_type: &str,
-    metadata: &Value,
 ) -> Result<()> {
-    let cache = cache.lock().await;
-    
-    if let Some(schema) = cache.get(&(source.to_string(), event_type.to_string())) {
-        validate_against_schema(metadata, schema)
-            .context("Metadata validation failed")?;
-        Ok(())
-    } else {
-        warn!("No schema found for {}:{}", source, event_type);
-        // Allow event without schema validation
-        Ok(())
+    let compiled = {
+        let db = db.lock().await;
+        load_one_schema(&db, source, event_type)?
+    };
+    let mut cache = cache.lock().await;
+    match compiled {
+        Some(compiled) => {
+            cache.insert((source.to_string(), event_type.to_string()), compiled);
+            debug!("Reloaded schema for {}:{}", source, event_type);
+        }
+        None => {
+            cache.remove(&(source.to_string(), event_type.to_string()));
+            debug!("Evicted schema for {}:{} (no longer registered)", source, event_type);
+        }
     }
+    Ok(())
+}
+
+/// Rebuild the entire cache from the database, discarding entries for schemas
+/// that have been removed. Suited to a periodic background refresh that keeps
+/// multi-process deployments consistent.
+pub(crate) async fn reload_all(db: &Arc<Mutex<Connection>>, cache: &SchemaCache) -> Result<()> {
+    let fresh = {
+        let db = db.lock().await;
+        load_all_schemas(&db)?
+    };
+    let mut cache = cache.lock().await;
+    *cache = fresh;
+    debug!("Reloaded all schemas ({} entries)", cache.len());
+    Ok(())
 }
 
-// Basic schema structure validation
-fn validate_schema_structure(schema: &Value) -> Result<()> {
-    // Schema must be an object
-    if !schema.is_object() {
-        return Err(anyhow::anyhow!("Schema must be a JSON object"));
+/// Delete every stored version of `(source, event_type)` and evict it from the
+/// cache. Subsequent events for the type validate against no schema until it is
+/// registered again.
+pub(crate) async fn unregister_event_type(
+    db: &Arc<Mutex<Connection>>,
+    cache: &SchemaCache,
+    source: &str,
+    event_type: &str,
+) -> Result<()> {
+    {
+        let db = db.lock().await;
+        db.execute(
+            "DELETE FROM event_types WHERE source = ? AND event_type = ?",
+            params![source, event_type],
+        )?;
     }
+    let mut cache = cache.lock().await;
+    cache.remove(&(source.to_string(), event_type.to_string()));
+    debug!("Unregistered schema for {}:{}", source, event_type);
+    Ok(())
+}
 
-    let obj = schema.as_object().unwrap();
+/// List the stored version history for `(source, event_type)`, oldest first,
+/// so callers can inspect or validate against a specific historical version.
+pub(crate) async fn list_schema_versions(
+    db: &Arc<Mutex<Connection>>,
+    source: &str,
+    event_type: &str,
+) -> Result<Vec<SchemaVersion>> {
+    let db = db.lock().await;
+    let mut stmt = db.prepare(
+        "SELECT version, schema, compatibility, format, created_at FROM event_types
+         WHERE source = ? AND event_type = ? ORDER BY version ASC",
+    )?;
+    let rows = stmt
+        .query_map(params![source, event_type], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    rows.into_iter()
+        .map(|(version, schema_str, compatibility, format, created_at)| {
+            Ok(SchemaVersion {
+                version,
+                schema: serde_json::from_str(&schema_str)?,
+                compatibility,
+                format,
+                created_at,
+            })
+        })
+        .collect()
+}
 
-    // Must have type field
-    if !obj.contains_key("type") {
-        return Err(anyhow::anyhow!("Schema must have 'type' field"));
-    }
+/// Validate `metadata` against a specific historical schema version, compiling
+/// that version on demand. Returns every violation, like the cached path.
+pub(crate) async fn validate_event_metadata_version(
+    db: &Arc<Mutex<Connection>>,
+    source: &str,
+    event_type: &str,
+    version: i64,
+    metadata: &Value,
+) -> Result<Vec<EventValidationError>> {
+    let versions = list_schema_versions(db, source, event_type).await?;
+    let entry = versions
+        .into_iter()
+        .find(|v| v.version == version)
+        .ok_or_else(|| anyhow::anyhow!("No schema version {version} for {source}:{event_type}"))?;
+    let format = SchemaFormat::parse(&entry.format)?;
+    let compiled = compile_schema(&entry.schema, format)?;
+    Ok(compiled.validate(metadata))
+}
 
-    // If it has properties, they must be an object
-    if let Some(props) = obj.get("properties") {
-        if !props.is_object() {
-            return Err(anyhow::anyhow!("Schema properties must be an object"));
+// Compute the breaking changes from `old` to `new` under `mode`, walking the
+// `properties`/`required`/`type` maps recursively. An empty result means the
+// change is compatible.
+fn check_compatibility(old: &Value, new: &Value, mode: CompatibilityMode) -> Vec<BreakingChange> {
+    let mut breaks = Vec::new();
+    match mode {
+        CompatibilityMode::None => {}
+        CompatibilityMode::Backward => compare_schemas(old, new, Direction::Backward, "", &mut breaks),
+        CompatibilityMode::Forward => compare_schemas(old, new, Direction::Forward, "", &mut breaks),
+        CompatibilityMode::Full => {
+            compare_schemas(old, new, Direction::Backward, "", &mut breaks);
+            compare_schemas(old, new, Direction::Forward, "", &mut breaks);
         }
     }
+    breaks
+}
 
-    Ok(())
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Backward,
+    Forward,
 }
 
-// Validate data against a JSON schema
-fn validate_against_schema(data: &Value, schema: &Value) -> Result<()> {
-    let schema_obj = schema.as_object()
-        .context("Schema must be an object")?;
+// Recursive diff of two object schemas. `Backward` reasons about "can the new
+// schema read data written under the old one"; `Forward` is the mirror.
+fn compare_schemas(
+    old: &Value,
+    new: &Value,
+    dir: Direction,
+    path: &str,
+    breaks: &mut Vec<BreakingChange>,
+) {
+    // Type narrowing. For Backward a narrower *new* type rejects old data; for
+    // Forward a narrower *old* type rejects new data.
+    if let (Some(old_ty), Some(new_ty)) = (type_of(old), type_of(new)) {
+        let narrowing = match dir {
+            Direction::Backward => is_narrowing(old_ty, new_ty),
+            Direction::Forward => is_narrowing(new_ty, old_ty),
+        };
+        if narrowing {
+            breaks.push(BreakingChange {
+                path: format!("{path}/type"),
+                detail: match dir {
+                    Direction::Backward => format!("type narrowed from `{old_ty}` to `{new_ty}`"),
+                    Direction::Forward => {
+                        format!("old type `{old_ty}` is narrower than new type `{new_ty}`")
+                    }
+                },
+            });
+        }
+    }
 
-    // Check type
-    if let Some(type_val) = schema_obj.get("type") {
-        let type_str = type_val.as_str()
-            .context("Schema type must be a string")?;
-        
-        match type_str {
-            "object" => {
-                if !data.is_object() {
-                    return Err(anyhow::anyhow!("Data must be an object"));
-                }
-                
-                // Validate properties if specified
-                if let Some(props) = schema_obj.get("properties") {
-                    validate_object_properties(data.as_object().unwrap(), props)?;
-                }
-            }
-            "array" => {
-                if !data.is_array() {
-                    return Err(anyhow::anyhow!("Data must be an array"));
-                }
-                
-                // Validate items if specified
-                if let Some(items) = schema_obj.get("items") {
-                    validate_array_items(data.as_array().unwrap(), items)?;
-                }
-            }
-            "string" => {
-                if !data.is_string() {
-                    return Err(anyhow::anyhow!("Data must be a string"));
+    let old_required = required_set(old);
+    let new_required = required_set(new);
+    let old_props = properties_of(old);
+    let new_props = properties_of(new);
+
+    match dir {
+        Direction::Backward => {
+            // A newly-required property with no default rejects old data that
+            // omitted it.
+            for name in new_required.iter() {
+                if !old_required.contains(name) {
+                    let has_default = new_props
+                        .and_then(|p| p.get(name))
+                        .map(|s| s.get("default").is_some())
+                        .unwrap_or(false);
+                    if !has_default {
+                        breaks.push(BreakingChange {
+                            path: format!("{path}/properties/{name}"),
+                            detail: "property became required without a default".to_string(),
+                        });
+                    }
                 }
             }
-            "number" => {
-                if !data.is_number() {
-                    return Err(anyhow::anyhow!("Data must be a number"));
-                }
-            }
-            "boolean" => {
-                if !data.is_boolean() {
-                    return Err(anyhow::anyhow!("Data must be a boolean"));
+        }
+        Direction::Forward => {
+            // A field required by the old schema but dropped from the new one
+            // leaves the old reader expecting a field the new writer omits.
+            for name in old_required.iter() {
+                if !new_required.contains(name) {
+                    breaks.push(BreakingChange {
+                        path: format!("{path}/properties/{name}"),
+                        detail: "required property removed".to_string(),
+                    });
                 }
             }
-            _ => {
-                warn!("Unsupported schema type: {}", type_str);
+        }
+    }
+
+    // Recurse into properties present in both schemas.
+    if let (Some(old_props), Some(new_props)) = (old_props, new_props) {
+        for (name, old_prop) in old_props {
+            if let Some(new_prop) = new_props.get(name) {
+                compare_schemas(
+                    old_prop,
+                    new_prop,
+                    dir,
+                    &format!("{path}/properties/{name}"),
+                    breaks,
+                );
             }
         }
     }
+}
 
-    Ok(())
+fn type_of(schema: &Value) -> Option<&str> {
+    schema.get("type").and_then(Value::as_str)
 }
 
-// Validate object properties against schema
-fn validate_object_properties(data: &serde_json::Map<String, Value>, schema: &Value) -> Result<()> {
-    let props = schema.as_object()
-        .context("Properties schema must be an object")?;
+// Is moving from `from` to `to` a narrowing (so `to` rejects some values
+// `from` accepted)? `integer` is a subset of `number`; unrelated types count
+// as narrowing because neither accepts the other's values.
+fn is_narrowing(from: &str, to: &str) -> bool {
+    if from == to {
+        return false;
+    }
+    match (from, to) {
+        ("number", "integer") => true,
+        ("integer", "number") => false,
+        _ => true,
+    }
+}
+
+fn required_set(schema: &Value) -> std::collections::HashSet<String> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn properties_of(schema: &Value) -> Option<&serde_json::Map<String, Value>> {
+    schema.get("properties").and_then(Value::as_object)
+}
+
+// Collect every violation a compiled validator reports for an instance.
+fn collect_errors(validator: &Validator, metadata: &Value) -> Vec<EventValidationError> {
+    validator
+        .iter_errors(metadata)
+        .map(|e| EventValidationError {
+            instance_path: e.instance_path.to_string(),
+            schema_path: e.schema_path.to_string(),
+            message: e.to_string(),
+        })
+        .collect()
+}
+
+// Validate event metadata against its schema
+pub(crate) async fn validate_event_metadata(
+    cache: &SchemaCache,
+    source: &str,
+    event_type: &str,
+    metadata: &Value,
+) -> Result<Vec<EventValidationError>> {
+    let cache = cache.lock().await;
+
+    // An explicit `$schema`/`$id` reference in the payload pins the event to an
+    // exact schema, supporting sources that emit several message shapes. A
+    // reference that fails to resolve is a hard error, unlike the permissive
+    // fallback below. The routing key is not part of the event body, so it is
+    // stripped before validation — otherwise a schema with
+    // `additionalProperties: false` (or a strict `required` set) would reject
+    // its own routing field.
+    if let Some(reference) = schema_reference(metadata) {
+        let stripped = strip_schema_reference(metadata);
+        return match resolve_reference(&cache, reference) {
+            Some(compiled) => Ok(compiled.validate(&stripped)),
+            None => Err(anyhow::anyhow!(
+                "Referenced schema `{reference}` is not registered"
+            )),
+        };
+    }
+
+    if let Some(compiled) = cache.get(&(source.to_string(), event_type.to_string())) {
+        // Collect every violation, not just the first, so producers see the
+        // full set of problems in one pass.
+        Ok(compiled.validate(metadata))
+    } else {
+        debug!("No schema found for {}:{}", source, event_type);
+        // No schema registered: nothing to validate against.
+        Ok(Vec::new())
+    }
+}
 
-    for (key, prop_schema) in props {
-        if let Some(value) = data.get(key) {
-            validate_against_schema(value, prop_schema)?;
+// Extract an explicit schema reference from a payload, if present. A reference
+// is either a `source:event_type` id or a bare logical name (the event_type).
+fn schema_reference(metadata: &Value) -> Option<&str> {
+    metadata
+        .get("$schema")
+        .or_else(|| metadata.get("$id"))
+        .and_then(Value::as_str)
+}
+
+// Return a copy of `metadata` with the `$schema`/`$id` routing keys removed so
+// they are not validated as part of the event body.
+fn strip_schema_reference(metadata: &Value) -> Value {
+    let mut cloned = metadata.clone();
+    if let Some(obj) = cloned.as_object_mut() {
+        obj.remove("$schema");
+        obj.remove("$id");
+    }
+    cloned
+}
+
+// Resolve a reference against the cache: a `source:event_type` id matches an
+// exact key, while a bare name matches any registered schema with that
+// event_type.
+fn resolve_reference<'a>(
+    cache: &'a HashMap<(String, String), CompiledSchema>,
+    reference: &str,
+) -> Option<&'a CompiledSchema> {
+    match reference.split_once(':') {
+        Some((source, event_type)) => {
+            cache.get(&(source.to_string(), event_type.to_string()))
         }
+        None => cache
+            .iter()
+            .find(|((_, event_type), _)| event_type == reference)
+            .map(|(_, compiled)| compiled),
     }
+}
 
-    Ok(())
+/// Encode event metadata into the compact Avro binary form for its registered
+/// schema. Errors if no schema is registered or the schema is JSON Schema
+/// rather than Avro, since only Avro defines a binary encoding.
+pub(crate) async fn encode_event_metadata(
+    cache: &SchemaCache,
+    source: &str,
+    event_type: &str,
+    metadata: &Value,
+) -> Result<Vec<u8>> {
+    let cache = cache.lock().await;
+    match cache.get(&(source.to_string(), event_type.to_string())) {
+        Some(CompiledSchema::Avro(schema)) => {
+            let value = avro_value(metadata)?
+                .resolve(schema)
+                .map_err(|e| anyhow::anyhow!("Metadata does not match Avro schema: {e}"))?;
+            apache_avro::to_avro_datum(schema, value)
+                .map_err(|e| anyhow::anyhow!("Avro encoding failed: {e}"))
+        }
+        Some(CompiledSchema::JsonSchema(_)) => Err(anyhow::anyhow!(
+            "Schema for {source}:{event_type} is JSON Schema, which has no binary encoding"
+        )),
+        None => Err(anyhow::anyhow!(
+            "No schema registered for {source}:{event_type}"
+        )),
+    }
 }
 
-// Validate array items against schema
-fn validate_array_items(data: &[Value], schema: &Value) -> Result<()> {
-    for item in data {
-        validate_against_schema(item, schema)?;
+/// Decode compact Avro binary metadata back into JSON using the registered
+/// schema. The inverse of [`encode_event_metadata`].
+pub(crate) async fn decode_event_metadata(
+    cache: &SchemaCache,
+    source: &str,
+    event_type: &str,
+    bytes: &[u8],
+) -> Result<Value> {
+    let cache = cache.lock().await;
+    match cache.get(&(source.to_string(), event_type.to_string())) {
+        Some(CompiledSchema::Avro(schema)) => {
+            let mut cursor = std::io::Cursor::new(bytes);
+            let value = apache_avro::from_avro_datum(schema, &mut cursor, None)
+                .map_err(|e| anyhow::anyhow!("Avro decoding failed: {e}"))?;
+            apache_avro::from_value::<Value>(&value)
+                .map_err(|e| anyhow::anyhow!("Avro value is not representable as JSON: {e}"))
+        }
+        Some(CompiledSchema::JsonSchema(_)) => Err(anyhow::anyhow!(
+            "Schema for {source}:{event_type} is JSON Schema, which has no binary encoding"
+        )),
+        None => Err(anyhow::anyhow!(
+            "No schema registered for {source}:{event_type}"
+        )),
     }
-    Ok(())
 }
 
 #[cfg(test)]
@@ -212,7 +706,7 @@ mod tests {
         )?;
 
         let db = Arc::new(Mutex::new(conn));
-        let cache = init_schema_cache(&db.lock().await?).await?;
+        let cache = init_schema_cache(&db.lock().await).await?;
 
         // Register test schema
         let schema = json!({
@@ -232,6 +726,8 @@ mod tests {
             "test",
             "test_event",
             schema,
+            CompatibilityMode::Backward,
+            SchemaFormat::JsonSchema,
         ).await?;
 
         // Test valid data
@@ -240,12 +736,13 @@ mod tests {
             "tags": ["test", "validation"]
         });
 
-        validate_event_metadata(
+        let errors = validate_event_metadata(
             &cache,
             "test",
             "test_event",
             &valid_data,
         ).await?;
+        assert!(errors.is_empty());
 
         // Test invalid data
         let invalid_data = json!({
@@ -253,12 +750,166 @@ mod tests {
             "tags": [1, 2, 3]
         });
 
-        assert!(validate_event_metadata(
+        let errors = validate_event_metadata(
             &cache,
             "test",
             "test_event",
             &invalid_data,
-        ).await.is_err());
+        ).await?;
+        assert!(!errors.is_empty());
+        // Every violation carries a JSON Pointer to its location.
+        assert!(errors.iter().all(|e| e.instance_path.starts_with('/')));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_schema_evolution_compatibility() -> Result<()> {
+        let dir = tempdir()?;
+        let db_path = dir.path().join("test.db");
+
+        let conn = db::init_database(db_path.to_str().unwrap(), "test-key")?;
+        let db = Arc::new(Mutex::new(conn));
+        let cache = init_schema_cache(&db.lock().await).await?;
+
+        let v1 = json!({
+            "type": "object",
+            "properties": { "name": { "type": "string" } }
+        });
+        register_event_type(&db, &cache, "test", "evt", v1, CompatibilityMode::Backward, SchemaFormat::JsonSchema).await?;
+
+        // Adding an optional property is backward-compatible.
+        let v2 = json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "count": { "type": "number" }
+            }
+        });
+        register_event_type(&db, &cache, "test", "evt", v2, CompatibilityMode::Backward, SchemaFormat::JsonSchema).await?;
+
+        // Making `count` required without a default breaks backward compat.
+        let v3 = json!({
+            "type": "object",
+            "required": ["count"],
+            "properties": {
+                "name": { "type": "string" },
+                "count": { "type": "number" }
+            }
+        });
+        assert!(
+            register_event_type(&db, &cache, "test", "evt", v3.clone(), CompatibilityMode::Backward, SchemaFormat::JsonSchema)
+                .await
+                .is_err()
+        );
+
+        // NONE accepts the same change and appends a third version.
+        register_event_type(&db, &cache, "test", "evt", v3, CompatibilityMode::None, SchemaFormat::JsonSchema).await?;
+
+        let versions = list_schema_versions(&db, "test", "evt").await?;
+        assert_eq!(versions.iter().map(|v| v.version).collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        // The first version had no `count`, so it does not constrain it.
+        let errors = validate_event_metadata_version(
+            &db,
+            "test",
+            "evt",
+            1,
+            &json!({ "name": "a", "count": "not a number" }),
+        )
+        .await?;
+        assert!(errors.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_avro_validate_and_roundtrip() -> Result<()> {
+        let dir = tempdir()?;
+        let db_path = dir.path().join("test.db");
+
+        let conn = db::init_database(db_path.to_str().unwrap(), "test-key")?;
+        let db = Arc::new(Mutex::new(conn));
+        let cache = init_schema_cache(&db.lock().await).await?;
+
+        let schema = json!({
+            "type": "record",
+            "name": "Sample",
+            "fields": [
+                { "name": "count", "type": "long" },
+                { "name": "label", "type": "string" }
+            ]
+        });
+        register_event_type(
+            &db,
+            &cache,
+            "test",
+            "avro_event",
+            schema,
+            CompatibilityMode::None,
+            SchemaFormat::Avro,
+        )
+        .await?;
+
+        let metadata = json!({ "count": 7, "label": "ok" });
+        let errors = validate_event_metadata(&cache, "test", "avro_event", &metadata).await?;
+        assert!(errors.is_empty());
+
+        // A field of the wrong type fails validation.
+        let bad = json!({ "count": "seven", "label": "ok" });
+        let errors = validate_event_metadata(&cache, "test", "avro_event", &bad).await?;
+        assert!(!errors.is_empty());
+
+        // Encoding then decoding round-trips the payload.
+        let encoded = encode_event_metadata(&cache, "test", "avro_event", &metadata).await?;
+        let decoded = decode_event_metadata(&cache, "test", "avro_event", &encoded).await?;
+        assert_eq!(decoded["label"], json!("ok"));
+        assert_eq!(decoded["count"], json!(7));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reload_and_unregister() -> Result<()> {
+        let dir = tempdir()?;
+        let db_path = dir.path().join("test.db");
+
+        let conn = db::init_database(db_path.to_str().unwrap(), "test-key")?;
+        let db = Arc::new(Mutex::new(conn));
+        let cache = init_schema_cache(&db.lock().await).await?;
+
+        // Simulate another process registering a schema by writing the row
+        // directly, bypassing the in-process cache.
+        let schema = json!({ "type": "object", "properties": { "x": { "type": "number" } } });
+        {
+            let conn = db.lock().await;
+            conn.execute(
+                "INSERT INTO event_types (source, event_type, schema, version, compatibility, format, created_at)
+                 VALUES (?, ?, ?, 1, 'BACKWARD', 'json-schema', strftime('%s', 'now'))",
+                params!["ext", "evt", schema.to_string()],
+            )?;
+        }
+
+        // Not visible until the cache is told to reload.
+        assert!(validate_event_metadata(&cache, "ext", "evt", &json!({ "x": "no" }))
+            .await?
+            .is_empty());
+        reload_schema(&db, &cache, "ext", "evt").await?;
+        assert!(!validate_event_metadata(&cache, "ext", "evt", &json!({ "x": "no" }))
+            .await?
+            .is_empty());
+
+        // Unregistering evicts the entry and deletes the row.
+        unregister_event_type(&db, &cache, "ext", "evt").await?;
+        assert!(validate_event_metadata(&cache, "ext", "evt", &json!({ "x": "no" }))
+            .await?
+            .is_empty());
+        assert!(list_schema_versions(&db, "ext", "evt").await?.is_empty());
+
+        // reload_all drops the now-absent entry too (a no-op here, but exercises
+        // the rebuild path).
+        reload_all(&db, &cache).await?;
+        assert!(cache.lock().await.is_empty());
 
         Ok(())
     }
@@ -0,0 +1,135 @@
+// Optional streaming export sink.
+//
+// Where `export` (Parquet) is a pull-based snapshot of the local store, this
+// is a push-based fan-out: each committed `Event`/`TextCapture` is also
+// produced to a message bus so downstream consumers see captures as they land
+// rather than only by querying the DB. The sink sits *after* the DB commit in
+// the flush path (produce-after-commit), and any records it fails to produce
+// are handed to the dead-letter path just like a failed DB flush, so nothing
+// is lost on a broker hiccup.
+//
+// Only the Kafka implementation exists today, behind the `export_kafka`
+// feature; the `ExportSink` trait keeps the flush path agnostic so other buses
+// can be added later.
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+
+use super::{Event, TextCapture};
+
+/// Connection and topic settings for the streaming export sink.
+#[derive(Debug, Clone)]
+pub struct ExportConfig {
+    /// Broker bootstrap list, e.g. `["localhost:9092"]`.
+    pub brokers: Vec<String>,
+    /// Topic events are produced to.
+    pub events_topic: String,
+    /// Topic text captures are produced to.
+    pub text_captures_topic: String,
+    /// Producer compression codec (`none`, `gzip`, `snappy`, `lz4`, `zstd`).
+    pub compression: String,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            brokers: vec![String::from("localhost:9092")],
+            events_topic: String::from("activity.events"),
+            text_captures_topic: String::from("activity.text_captures"),
+            compression: String::from("lz4"),
+        }
+    }
+}
+
+/// Produces captured records to a downstream bus. Records are keyed by
+/// `partition_key` so a given month's data lands on consistent partitions.
+#[async_trait]
+pub trait ExportSink: Send + Sync {
+    async fn export_events(&self, events: &[Event]) -> Result<()>;
+    async fn export_text_captures(&self, captures: &[TextCapture]) -> Result<()>;
+}
+
+/// Open the sink backing `config`. Errors unless a sink feature is enabled.
+pub fn open(config: &ExportConfig) -> Result<Box<dyn ExportSink>> {
+    open_kafka(config)
+}
+
+#[cfg(feature = "export_kafka")]
+fn open_kafka(config: &ExportConfig) -> Result<Box<dyn ExportSink>> {
+    Ok(Box::new(kafka_impl::KafkaSink::open(config)?))
+}
+
+#[cfg(not(feature = "export_kafka"))]
+fn open_kafka(_config: &ExportConfig) -> Result<Box<dyn ExportSink>> {
+    bail!("streaming export requested but the `export_kafka` feature is not enabled")
+}
+
+#[cfg(feature = "export_kafka")]
+mod kafka_impl {
+    use super::*;
+    use rdkafka::config::ClientConfig;
+    use rdkafka::producer::{FutureProducer, FutureRecord};
+    use std::time::Duration;
+
+    pub(crate) struct KafkaSink {
+        producer: FutureProducer,
+        events_topic: String,
+        text_captures_topic: String,
+    }
+
+    impl KafkaSink {
+        pub fn open(config: &ExportConfig) -> Result<Self> {
+            let producer: FutureProducer = ClientConfig::new()
+                .set("bootstrap.servers", config.brokers.join(","))
+                .set("compression.codec", &config.compression)
+                .create()?;
+            Ok(Self {
+                producer,
+                events_topic: config.events_topic.clone(),
+                text_captures_topic: config.text_captures_topic.clone(),
+            })
+        }
+
+        // Produce a JSON batch, keyed by partition_key, awaiting all deliveries.
+        async fn produce(&self, topic: &str, records: Vec<(String, String)>) -> Result<()> {
+            for (key, payload) in &records {
+                self.producer
+                    .send(
+                        FutureRecord::to(topic).key(key).payload(payload),
+                        Duration::from_secs(5),
+                    )
+                    .await
+                    .map_err(|(e, _)| anyhow::anyhow!("Kafka produce to {topic} failed: {e}"))?;
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl ExportSink for KafkaSink {
+        async fn export_events(&self, events: &[Event]) -> Result<()> {
+            let records = events
+                .iter()
+                .map(|e| {
+                    Ok((
+                        e.partition_key.clone().unwrap_or_default(),
+                        serde_json::to_string(e)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            self.produce(&self.events_topic, records).await
+        }
+
+        async fn export_text_captures(&self, captures: &[TextCapture]) -> Result<()> {
+            let records = captures
+                .iter()
+                .map(|c| {
+                    Ok((
+                        c.partition_key.clone().unwrap_or_default(),
+                        serde_json::to_string(c)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            self.produce(&self.text_captures_topic, records).await
+        }
+    }
+}
@@ -0,0 +1,227 @@
+// Optional OpenTelemetry telemetry for the capture and retention pipelines.
+//
+// Spans are exported over OTLP (Jaeger-compatible) via a `tracing`-
+// OpenTelemetry layer, and metrics are exposed on a Prometheus scrape
+// endpoint. Instruments cover captures ingested per source/app, backpressure
+// on the capture channel, retention records deleted/summarized, migration
+// status and on-disk DB size.
+//
+// The whole subsystem is gated behind the `telemetry` cargo feature *and* a
+// runtime config toggle, so privacy-sensitive users can keep it off. When the
+// feature is disabled every recording helper compiles down to a no-op.
+use anyhow::Result;
+
+/// Runtime telemetry configuration. `enabled` gates the subsystem even when
+/// the `telemetry` feature is compiled in.
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    pub enabled: bool,
+    /// OTLP endpoint for span export (e.g. `http://localhost:4317`).
+    pub otlp_endpoint: String,
+    /// Address to serve the Prometheus `/metrics` scrape endpoint on.
+    pub prometheus_addr: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            otlp_endpoint: String::from("http://localhost:4317"),
+            prometheus_addr: String::from("127.0.0.1:9464"),
+        }
+    }
+}
+
+#[cfg(feature = "telemetry")]
+mod imp {
+    use super::*;
+    use opentelemetry::metrics::{Counter, Gauge, Meter};
+    use opentelemetry::KeyValue;
+    use std::sync::OnceLock;
+    use tracing::info;
+
+    // Process-wide instruments, initialised once by `init`.
+    struct Instruments {
+        captures_ingested: Counter<u64>,
+        captures_dropped: Counter<u64>,
+        captures_redacted: Counter<u64>,
+        channel_backpressure: Gauge<u64>,
+        retention_deleted: Counter<u64>,
+        retention_summarized: Counter<u64>,
+        migration_version: Gauge<u64>,
+        db_size_bytes: Gauge<u64>,
+    }
+
+    static INSTRUMENTS: OnceLock<Instruments> = OnceLock::new();
+
+    fn build_instruments(meter: &Meter) -> Instruments {
+        Instruments {
+            captures_ingested: meter
+                .u64_counter("captures.ingested")
+                .with_description("Captures ingested, labelled by source and app")
+                .init(),
+            captures_dropped: meter
+                .u64_counter("captures.dropped")
+                .with_description("Captures dropped by the app/secure-field filter, labelled by app")
+                .init(),
+            captures_redacted: meter
+                .u64_counter("captures.redacted")
+                .with_description("Captures with secrets masked, labelled by app")
+                .init(),
+            channel_backpressure: meter
+                .u64_gauge("capture.channel.backpressure")
+                .with_description("Queued items on the capture channel")
+                .init(),
+            retention_deleted: meter
+                .u64_counter("retention.records.deleted")
+                .init(),
+            retention_summarized: meter
+                .u64_counter("retention.records.summarized")
+                .init(),
+            migration_version: meter.u64_gauge("db.migration.version").init(),
+            db_size_bytes: meter.u64_gauge("db.size.bytes").init(),
+        }
+    }
+
+    /// Initialise the tracing-OpenTelemetry layer and Prometheus exporter.
+    pub fn init(config: &TelemetryConfig) -> Result<()> {
+        use opentelemetry_otlp::WithExportConfig;
+
+        // Span export over OTLP.
+        let tracer = opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(
+                opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(config.otlp_endpoint.clone()),
+            )
+            .install_batch(opentelemetry_sdk::runtime::Tokio)?;
+        let telemetry_layer = tracing_opentelemetry::layer().with_tracer(tracer);
+
+        // Metrics via a Prometheus exporter scraped at `prometheus_addr`.
+        let registry = prometheus::Registry::new();
+        let exporter = opentelemetry_prometheus::exporter()
+            .with_registry(registry.clone())
+            .build()?;
+        let provider = opentelemetry_sdk::metrics::SdkMeterProvider::builder()
+            .with_reader(exporter)
+            .build();
+        opentelemetry::global::set_meter_provider(provider.clone());
+        let meter = opentelemetry::global::meter("activity-tracker");
+        let _ = INSTRUMENTS.set(build_instruments(&meter));
+
+        serve_prometheus(registry, config.prometheus_addr.clone());
+
+        use tracing_subscriber::layer::SubscriberExt;
+        use tracing_subscriber::util::SubscriberInitExt;
+        tracing_subscriber::registry()
+            .with(tracing_subscriber::fmt::layer())
+            .with(telemetry_layer)
+            .try_init()
+            .ok();
+
+        info!("Telemetry initialised (OTLP + Prometheus)");
+        Ok(())
+    }
+
+    fn serve_prometheus(registry: prometheus::Registry, addr: String) {
+        tokio::spawn(async move {
+            use prometheus::{Encoder, TextEncoder};
+            // A minimal scrape endpoint; a real deployment would reuse the
+            // service's own HTTP server.
+            if let Ok(listener) = tokio::net::TcpListener::bind(&addr).await {
+                loop {
+                    if let Ok((mut stream, _)) = listener.accept().await {
+                        let encoder = TextEncoder::new();
+                        let mut buf = Vec::new();
+                        let _ = encoder.encode(&registry.gather(), &mut buf);
+                        use tokio::io::AsyncWriteExt;
+                        let header = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+                            encoder.format_type(),
+                            buf.len()
+                        );
+                        let _ = stream.write_all(header.as_bytes()).await;
+                        let _ = stream.write_all(&buf).await;
+                    }
+                }
+            }
+        });
+    }
+
+    pub fn record_capture(source: &str, app: &str) {
+        if let Some(i) = INSTRUMENTS.get() {
+            i.captures_ingested.add(
+                1,
+                &[
+                    KeyValue::new("source", source.to_string()),
+                    KeyValue::new("app", app.to_string()),
+                ],
+            );
+        }
+    }
+
+    pub fn record_capture_dropped(app: &str) {
+        if let Some(i) = INSTRUMENTS.get() {
+            i.captures_dropped
+                .add(1, &[KeyValue::new("app", app.to_string())]);
+        }
+    }
+
+    pub fn record_capture_redacted(app: &str) {
+        if let Some(i) = INSTRUMENTS.get() {
+            i.captures_redacted
+                .add(1, &[KeyValue::new("app", app.to_string())]);
+        }
+    }
+
+    pub fn record_channel_backpressure(channel: &str, queued: u64) {
+        if let Some(i) = INSTRUMENTS.get() {
+            i.channel_backpressure
+                .record(queued, &[KeyValue::new("channel", channel.to_string())]);
+        }
+    }
+
+    pub fn record_retention(source: &str, deleted: u64, summarized: u64) {
+        if let Some(i) = INSTRUMENTS.get() {
+            let labels = [KeyValue::new("source", source.to_string())];
+            i.retention_deleted.add(deleted, &labels);
+            i.retention_summarized.add(summarized, &labels);
+        }
+    }
+
+    pub fn record_migration_version(version: u64) {
+        if let Some(i) = INSTRUMENTS.get() {
+            i.migration_version.record(version, &[]);
+        }
+    }
+
+    pub fn record_db_size(bytes: u64) {
+        if let Some(i) = INSTRUMENTS.get() {
+            i.db_size_bytes.record(bytes, &[]);
+        }
+    }
+}
+
+// No-op shims when the `telemetry` feature is disabled, so call sites need no
+// `cfg` of their own.
+#[cfg(not(feature = "telemetry"))]
+mod imp {
+    use super::*;
+
+    pub fn init(_config: &TelemetryConfig) -> Result<()> {
+        Ok(())
+    }
+    pub fn record_capture(_source: &str, _app: &str) {}
+    pub fn record_capture_dropped(_app: &str) {}
+    pub fn record_capture_redacted(_app: &str) {}
+    pub fn record_channel_backpressure(_channel: &str, _queued: u64) {}
+    pub fn record_retention(_source: &str, _deleted: u64, _summarized: u64) {}
+    pub fn record_migration_version(_version: u64) {}
+    pub fn record_db_size(_bytes: u64) {}
+}
+
+pub use imp::{
+    init, record_capture, record_capture_dropped, record_capture_redacted,
+    record_channel_backpressure, record_db_size, record_migration_version, record_retention,
+};
@@ -60,11 +60,16 @@ pub struct TextCaptureSystem {
     sender: mpsc::Sender<CaptureOperation>,
     window_tracker: Arc<Mutex<WindowTracker>>,
     accessibility_handler: Box<dyn AccessibilityHandler>,
+    filter: super::capture_filter::CaptureFilter,
 }
 
 impl TextCaptureSystem {
-    pub fn new(sender: mpsc::Sender<CaptureOperation>) -> Result<Self> {
+    pub fn new(
+        sender: mpsc::Sender<CaptureOperation>,
+        filter_config: super::capture_filter::FilterConfig,
+    ) -> Result<Self> {
         let window_tracker = Arc::new(Mutex::new(WindowTracker::new()?));
+        let filter = super::capture_filter::CaptureFilter::new(filter_config)?;
         
         // Initialize OS-specific accessibility handler
         let accessibility_handler: Box<dyn AccessibilityHandler> = {
@@ -82,6 +87,7 @@ impl TextCaptureSystem {
             sender,
             window_tracker,
             accessibility_handler,
+            filter,
         })
     }
 
@@ -114,6 +120,29 @@ impl TextCaptureSystem {
         let source_context = self.accessibility_handler
             .get_element_context(&event)?;
 
+        // Filter stage: drop captures from denied/secure apps or fields, and
+        // redact secrets from anything that survives, before it leaves the
+        // process.
+        use super::capture_filter::FilterOutcome;
+        let text_content = match self.filter.apply(
+            &window_info.application_name,
+            window_info.window_class.as_deref(),
+            &source_context.element_type,
+            &source_context.element_role,
+            &text_content,
+        ) {
+            FilterOutcome::Drop => {
+                super::telemetry::record_capture_dropped(&window_info.application_name);
+                return Ok(());
+            }
+            FilterOutcome::Keep { text, redacted } => {
+                if redacted {
+                    super::telemetry::record_capture_redacted(&window_info.application_name);
+                }
+                text
+            }
+        };
+
         // Create capture operation
         let capture = CaptureOperation {
             window_info,
@@ -122,6 +151,16 @@ impl TextCaptureSystem {
             source_context,
         };
 
+        // Record ingestion and current channel backpressure before sending.
+        super::telemetry::record_capture(
+            "accessibility",
+            &capture.window_info.application_name,
+        );
+        super::telemetry::record_channel_backpressure(
+            "capture",
+            (self.sender.max_capacity() - self.sender.capacity()) as u64,
+        );
+
         // Send for processing
         self.sender.send(capture).await
             .context("Failed to send capture operation")?;
@@ -217,7 +256,7 @@ mod tests {
     #[tokio::test]
     async fn test_capture_system() -> Result<()> {
         let (tx, mut rx) = mpsc::channel(100);
-        let system = TextCaptureSystem::new(tx)?;
+        let system = TextCaptureSystem::new(tx, super::super::capture_filter::FilterConfig::default())?;
 
         system.start_monitoring().await?;
 